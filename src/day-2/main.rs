@@ -1,210 +1,48 @@
-use anyhow::{anyhow, Context, Error, Result};
+use anyhow::{anyhow, Result};
 
-use advent_2019_common::{run_day_puzzle_solver, DayPuzzlePart};
-
-type Scalar = usize;
-
-#[derive(Clone, Debug)]
-struct MemoryBank {
-    tape: Vec<Scalar>,
-}
-
-impl TryFrom<String> for MemoryBank {
-    type Error = Error;
-
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        let split = value.split(',');
-        let mut tape = Vec::with_capacity(split.size_hint().0);
-        for part in split {
-            let part_scalar = part
-                .parse()
-                .with_context(|| format!("cannot parse tape scalar: {}", part))?;
-            tape.push(part_scalar);
-        }
-        Ok(Self { tape })
-    }
-}
-
-impl MemoryBank {
-    pub fn new(tape: Vec<Scalar>) -> Self {
-        Self { tape }
-    }
-
-    pub fn get_scalar_at(&self, index: usize) -> Result<Scalar> {
-        self.tape
-            .get(index)
-            .with_context(|| format!("Memory overflow on read at index {}", index))
-            .copied()
-    }
-
-    pub fn set_scalar_at(&mut self, index: usize, value: Scalar) -> Result<()> {
-        let reference = self
-            .tape
-            .get_mut(index)
-            .with_context(|| format!("Memory overflow on write at index {}", index))?;
-        *reference = value;
-        Ok(())
-    }
-
-    pub fn raw(&self) -> &Vec<Scalar> {
-        &self.tape
-    }
-}
-
-struct VirtualMachine {
-    program_counter: usize,
-    memory: MemoryBank,
-}
-
-const OPERATION_CODE_ADD: Scalar = 1;
-const OPERATION_CODE_MULTIPLY: Scalar = 2;
-const OPERATION_CODE_HALT: Scalar = 99;
-
-impl VirtualMachine {
-    pub fn from_tape(tape: &[Scalar]) -> Self {
-        Self {
-            memory: MemoryBank::new(tape.to_vec()),
-            program_counter: 0,
-        }
-    }
-
-    pub fn reset(&mut self, tape: &[Scalar]) {
-        self.memory = MemoryBank::new(tape.to_vec());
-        self.program_counter = 0;
-    }
-
-    pub fn run(&mut self) -> Result<()> {
-        'vm: loop {
-            if self.step()? {
-                break 'vm;
-            }
-        }
-        Ok(())
-    }
-
-    /// Returns true if the program must be halted.
-    pub fn step(&mut self) -> Result<bool> {
-        let current_step = self.memory.get_scalar_at(self.program_counter)?;
-        let decoded_operation =
-            Instruction::decode(self.program_counter, current_step, &self.memory)?;
-        if decoded_operation.apply(&mut self.memory)? {
-            return Ok(true);
-        }
-        self.program_counter += 4;
-        Ok(false)
-    }
-
-    pub fn program_counter_snapshot(&self) -> usize {
-        self.program_counter
-    }
-
-    pub fn memory_snapshot(&self) -> &MemoryBank {
-        &self.memory
-    }
-}
-
-#[derive(Debug)]
-enum Instruction {
-    /// Structure: (lhs_at, rhs_at, output_at)
-    Add(usize, usize, usize),
-    /// Structure: (lhs_at, rhs_at, output_at)
-    Multiply(usize, usize, usize),
-    /// Immediately halts the program.
-    Halt,
-}
-
-impl Instruction {
-    pub fn code(&self) -> Scalar {
-        match *self {
-            Instruction::Add(_, _, _) => OPERATION_CODE_ADD,
-            Instruction::Multiply(_, _, _) => OPERATION_CODE_MULTIPLY,
-            Instruction::Halt => OPERATION_CODE_HALT,
-        }
-    }
-
-    pub fn decode(pc: usize, code: Scalar, memory: &MemoryBank) -> Result<Self> {
-        let is_add = match code {
-            OPERATION_CODE_ADD => true,
-            OPERATION_CODE_MULTIPLY => false,
-            OPERATION_CODE_HALT => return Ok(Instruction::Halt),
-            _ => return Err(anyhow!("Operation::decode unknown opcode {}", code)),
-        };
-
-        let (lhs_at, rhs_at, output_at) = (
-            memory.get_scalar_at(pc + 1)?,
-            memory.get_scalar_at(pc + 2)?,
-            memory.get_scalar_at(pc + 3)?,
-        );
-
-        Ok((if is_add {
-            Instruction::Add
-        } else {
-            Instruction::Multiply
-        })(lhs_at, rhs_at, output_at))
-    }
-
-    /// Returns true for a `HALT` opcode.
-    pub fn apply(&self, memory: &mut MemoryBank) -> Result<bool> {
-        Ok(match *self {
-            Instruction::Add(lhs_at, rhs_at, output_at) => {
-                let (lhs, rhs) = (memory.get_scalar_at(lhs_at)?, memory.get_scalar_at(rhs_at)?);
-                let result = lhs + rhs;
-                memory.set_scalar_at(output_at, result)?;
-                false
-            }
-            Instruction::Multiply(lhs_at, rhs_at, output_at) => {
-                let (lhs, rhs) = (memory.get_scalar_at(lhs_at)?, memory.get_scalar_at(rhs_at)?);
-                let result = lhs * rhs;
-                memory.set_scalar_at(output_at, result)?;
-                false
-            }
-            Instruction::Halt => true,
-        })
-    }
-}
+use advent_2019_common::{
+    intcode::{MemoryBank, ParameterSearch, Scalar, VirtualMachine},
+    run_day_puzzle_solver, DayPuzzlePart,
+};
 
 fn compute_solution_1(tape: &[Scalar]) -> Result<Scalar> {
     let mut vm = VirtualMachine::from_tape(tape);
-    vm.run()?;
-    vm.memory_snapshot().get_scalar_at(0)
+    vm.run_until_halt()?;
+    Ok(vm.memory_snapshot().get_scalar_at(0))
 }
 
 const COMPUTE_SOLUTION_2_TARGET: Scalar = 19690720;
+/// The gravity assist program is straight-line, so a few hundred steps at most.
+const COMPUTE_SOLUTION_2_STEP_LIMIT: u64 = 10_000;
 
 fn compute_solution_2(tape: &[Scalar]) -> Result<Scalar> {
-    // brute-force
-    let mut vm = VirtualMachine::from_tape(&[]);
-    for noun in 0..100 {
-        for verb in 0..100 {
-            let mut vm_tape = tape.to_vec();
-            *vm_tape.get_mut(1).unwrap() = noun;
-            *vm_tape.get_mut(2).unwrap() = verb;
-            vm.reset(&vm_tape);
-            vm.run()?;
-            if vm.memory_snapshot().get_scalar_at(0)? == COMPUTE_SOLUTION_2_TARGET {
-                return Ok(100 * noun + verb);
-            }
-        }
+    let found = ParameterSearch::new(MemoryBank::new(tape.to_vec()))
+        .parameter(1, 0..100)
+        .parameter(2, 0..100)
+        .step_limit(COMPUTE_SOLUTION_2_STEP_LIMIT)
+        .decode_cache()
+        .find_first(COMPUTE_SOLUTION_2_TARGET)?;
+    match found.as_deref() {
+        Some(&[noun, verb]) => Ok(100 * noun + verb),
+        _ => Err(anyhow!(
+            "compute_solution_2: could not find solution in problem space"
+        )),
     }
-
-    Err(anyhow!(
-        "compute_solution_2: could not find solution in problem space"
-    ))
 }
 
 fn main() -> Result<()> {
     // Part 1
     let _ = run_day_puzzle_solver(2, DayPuzzlePart::One, b'\n', |input: Vec<MemoryBank>| {
         let mut memory_bank = input[0].clone();
-        memory_bank.set_scalar_at(1, 12)?;
-        memory_bank.set_scalar_at(2, 2)?;
-        Ok(compute_solution_1(memory_bank.raw()))
+        memory_bank.set_scalar_at(1, 12);
+        memory_bank.set_scalar_at(2, 2);
+        Ok(compute_solution_1(&memory_bank.raw()))
     })?;
 
     // Part 2
     let _ = run_day_puzzle_solver(2, DayPuzzlePart::Two, b'\n', |input: Vec<MemoryBank>| {
         let memory_bank = input[0].clone();
-        Ok(compute_solution_2(memory_bank.raw()))
+        Ok(compute_solution_2(&memory_bank.raw()))
     })?;
 
     Ok(())
@@ -212,61 +50,72 @@ fn main() -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use crate::VirtualMachine;
-
-    #[test]
-    fn test_day_2_virtual_machine_stepping() {
-        let tape_1 = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
-        let mut vm = VirtualMachine::from_tape(&tape_1);
-        assert_eq!(vm.memory_snapshot().raw(), &tape_1);
-
-        let tape_2 = [1, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50];
-        vm.step().unwrap();
-        assert_eq!(vm.memory_snapshot().raw(), &tape_2);
-
-        let tape_3 = [3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50];
-        vm.step().unwrap();
-        assert_eq!(vm.memory_snapshot().raw(), &tape_3);
+    use anyhow::{anyhow, Result};
+
+    use advent_2019_common::{
+        day_input_path,
+        intcode::{symbolic_result, MemoryBank, ParameterSearch, Scalar, VirtualMachine},
+    };
+
+    use crate::{compute_solution_1, compute_solution_2, COMPUTE_SOLUTION_2_TARGET};
+
+    /// Every valid (noun, verb) pair producing `target`, found by solving the
+    /// symbolic expression the program leaves at address 0.
+    fn solve_noun_verb_symbolically(
+        tape: &[Scalar],
+        target: Scalar,
+    ) -> Result<Vec<(Scalar, Scalar)>> {
+        let polynomial = symbolic_result(&MemoryBank::new(tape.to_vec()), 1, 2, 0)?;
+        polynomial.solve(target, 0..100, 0..100)
+    }
+
+    /// Same as `compute_solution_2`, without running the program once per pair.
+    fn compute_solution_2_symbolic(tape: &[Scalar]) -> Result<Scalar> {
+        match solve_noun_verb_symbolically(tape, COMPUTE_SOLUTION_2_TARGET)?.first() {
+            Some((noun, verb)) => Ok(100 * noun + verb),
+            None => Err(anyhow!(
+                "compute_solution_2_symbolic: could not find solution in problem space"
+            )),
+        }
     }
 
     #[test]
-    fn test_day_2_virtual_machine_running() {
-        let mut vm1 = VirtualMachine::from_tape(&[1, 0, 0, 0, 99]);
-        vm1.run().unwrap();
-        assert_eq!(vm1.memory_snapshot().raw(), &[2, 0, 0, 0, 99]);
-
-        let mut vm2 = VirtualMachine::from_tape(&[2, 3, 0, 3, 99]);
-        vm2.run().unwrap();
-        assert_eq!(vm2.memory_snapshot().raw(), &[2, 3, 0, 6, 99]);
-
-        let mut vm3 = VirtualMachine::from_tape(&[2, 4, 4, 5, 99, 0]);
-        vm3.run().unwrap();
-        assert_eq!(vm3.memory_snapshot().raw(), &[2, 4, 4, 5, 99, 9801]);
-
-        let mut vm4 = VirtualMachine::from_tape(&[1, 1, 1, 4, 99, 5, 6, 0, 99]);
-        vm4.run().unwrap();
-        assert_eq!(vm4.memory_snapshot().raw(), &[30, 1, 1, 4, 2, 5, 6, 0, 99]);
+    fn test_day_2_solution_1_runs_until_halt() {
+        // outputs a value before writing the result
+        assert_eq!(compute_solution_1(&[104, 7, 1101, 1, 1, 0, 99]).unwrap(), 2);
+        assert!(compute_solution_1(&[3, 0, 99]).is_err());
     }
 
     #[test]
-    fn test_day_2_virtual_machine_bug() {
-        let tape_1 = [1, 1, 1, 4, 99, 5, 6, 0, 99];
-        let mut vm = VirtualMachine::from_tape(&tape_1);
-        assert_eq!(vm.program_counter_snapshot(), 0);
-        assert_eq!(vm.memory_snapshot().raw(), &tape_1);
-
-        vm.step().unwrap();
-        assert_eq!(vm.program_counter_snapshot(), 4);
+    fn test_day_2_symbolic_solver_matches_brute_force() {
+        let tape = MemoryBank::from_file(day_input_path(2)).unwrap().raw();
         assert_eq!(
-            vm.memory_snapshot().raw(),
-            &vec![1, 1, 1, 4, 2, 5, 6, 0, 99]
+            compute_solution_2_symbolic(&tape).unwrap(),
+            compute_solution_2(&tape).unwrap()
         );
 
-        vm.step().unwrap();
-        assert_eq!(vm.program_counter_snapshot(), 8);
-        assert_eq!(
-            vm.memory_snapshot().raw(),
-            &vec![30, 1, 1, 4, 2, 5, 6, 0, 99]
-        );
+        let search = ParameterSearch::new(MemoryBank::new(tape.clone()))
+            .parameter(1, 0..100)
+            .parameter(2, 0..100);
+        for target in [19690720, 4462686, 1234] {
+            let symbolic: Vec<Vec<i64>> = solve_noun_verb_symbolically(&tape, target)
+                .unwrap()
+                .into_iter()
+                .map(|(noun, verb)| vec![noun, verb])
+                .collect();
+            assert_eq!(symbolic, search.find_all(target).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_day_2_only_patches_executed_code() {
+        let tape = MemoryBank::from_file(day_input_path(2)).unwrap().raw();
+        let mut vm = VirtualMachine::from_tape(&tape).with_self_modification_detection();
+        vm.run_until_halt().unwrap();
+
+        let detector = vm.self_modification().unwrap();
+        assert!(!detector.writes().is_empty());
+        // every word the program writes into was already executed
+        assert!(detector.writes().iter().all(|write| !write.executed_after));
     }
 }
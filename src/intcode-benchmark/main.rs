@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use advent_2019_common::{
+    day_input_path,
+    intcode::{MemoryBank, ParameterSearch},
+};
+
+/// Output searched for by day 2 part two.
+const DAY_2_TARGET: i64 = 19690720;
+
+/// Runs of each configuration; the fastest one is reported.
+const BENCHMARK_REPEATS: usize = 30;
+
+/// Times `search` exhausting the space, returning the number of matches.
+fn time_search(search: &ParameterSearch) -> Result<(Duration, usize)> {
+    let start = Instant::now();
+    let matches = search.find_all(DAY_2_TARGET)?.len();
+    Ok((start.elapsed(), matches))
+}
+
+/// Usage: `intcode-benchmark`
+///
+/// Compares the day 2 noun/verb search, on a single thread, with and without
+/// the instruction decode cache. Best run with `--release`.
+fn main() -> Result<()> {
+    let search = ParameterSearch::new(MemoryBank::from_file(day_input_path(2))?)
+        .parameter(1, 0..100)
+        .parameter(2, 0..100)
+        .threads(1);
+    println!("day 2 search: {} runs", search.len());
+
+    // alternating both configurations spreads the machine noise over them
+    let cached_search = search.clone().decode_cache();
+    let (mut uncached, mut cached) = (Duration::MAX, Duration::MAX);
+    for _ in 0..BENCHMARK_REPEATS {
+        let (elapsed, uncached_matches) = time_search(&search)?;
+        uncached = uncached.min(elapsed);
+        let (elapsed, cached_matches) = time_search(&cached_search)?;
+        cached = cached.min(elapsed);
+        assert_eq!(uncached_matches, cached_matches);
+    }
+    println!("  without decode cache: {:?}", uncached);
+    println!("  with decode cache:    {:?}", cached);
+
+    println!(
+        "=> speedup: {:.2}x",
+        uncached.as_secs_f64() / cached.as_secs_f64()
+    );
+
+    Ok(())
+}
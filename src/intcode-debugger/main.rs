@@ -0,0 +1,20 @@
+use std::{env, io};
+
+use anyhow::Result;
+
+use advent_2019_common::{
+    intcode::{Debugger, MemoryBank},
+    tape_path_from_arg,
+};
+
+/// Usage: `intcode-debugger <day number | tape file>`, then `help` at the prompt.
+fn main() -> Result<()> {
+    let path = tape_path_from_arg(
+        env::args().nth(1),
+        "intcode-debugger <day number | tape file>",
+    )?;
+
+    let memory = MemoryBank::from_file(&path)?;
+    let mut debugger = Debugger::new(&memory.raw());
+    debugger.repl(io::stdin().lock(), io::stdout(), true)
+}
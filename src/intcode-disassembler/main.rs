@@ -0,0 +1,21 @@
+use std::env;
+
+use anyhow::Result;
+
+use advent_2019_common::{
+    intcode::{render_disassembly, MemoryBank},
+    tape_path_from_arg,
+};
+
+/// Usage: `intcode-disassembler <day number | tape file>`
+fn main() -> Result<()> {
+    let path = tape_path_from_arg(
+        env::args().nth(1),
+        "intcode-disassembler <day number | tape file>",
+    )?;
+
+    let memory = MemoryBank::from_file(&path)?;
+    print!("{}", render_disassembly(&memory));
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use std::env;
+
+use anyhow::{anyhow, Result};
+
+use advent_2019_common::{
+    intcode::{ControlFlowGraph, MemoryBank},
+    tape_path_from_arg,
+};
+
+/// Usage: `intcode-flow-graph <day number | tape file> [entry address...]`
+///
+/// Prints the control-flow graph of the tape in the Graphviz DOT language,
+/// e.g. to be piped into `dot -Tsvg`. Entry addresses default to 0.
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let path = tape_path_from_arg(
+        args.next(),
+        "intcode-flow-graph <day number | tape file> [entry address...]",
+    )?;
+    let mut entries = args
+        .map(|entry| {
+            entry
+                .parse()
+                .map_err(|_| anyhow!("invalid entry address: {}", entry))
+        })
+        .collect::<Result<Vec<usize>>>()?;
+    if entries.is_empty() {
+        entries.push(0);
+    }
+
+    let memory = MemoryBank::from_file(&path)?;
+    print!(
+        "{}",
+        ControlFlowGraph::from_entries(&memory, &entries).to_dot()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::{env, fs};
+
+use anyhow::Result;
+
+use advent_2019_common::{
+    intcode::{translate, MemoryBank},
+    tape_path_from_arg,
+};
+
+/// Usage: `intcode-translator <day number | tape file> [output file]`
+///
+/// Writes the Rust translation of the tape to the output file, or to the
+/// standard output.
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let path = tape_path_from_arg(
+        args.next(),
+        "intcode-translator <day number | tape file> [output file]",
+    )?;
+
+    let source = translate(&MemoryBank::from_file(&path)?);
+    match args.next() {
+        Some(output_path) => fs::write(output_path, source)?,
+        None => print!("{}", source),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[rustfmt::skip]
+mod translated_day_2;
+#[cfg(test)]
+#[rustfmt::skip]
+mod translated_day_5;
+
+#[cfg(test)]
+mod tests {
+    use advent_2019_common::{
+        day_input_path,
+        intcode::{translate, MemoryBank, QueueDevice, VirtualMachine},
+    };
+
+    use crate::{translated_day_2, translated_day_5};
+
+    #[test]
+    fn test_translations_are_up_to_date() {
+        for (day_number, translation) in [
+            (2, include_str!("translated_day_2.rs")),
+            (5, include_str!("translated_day_5.rs")),
+        ] {
+            let memory = MemoryBank::from_file(day_input_path(day_number)).unwrap();
+            assert!(
+                translate(&memory) == translation,
+                "run `cargo run --bin intcode-translator {0} src/intcode-translator/translated_day_{0}.rs`",
+                day_number
+            );
+        }
+    }
+
+    #[test]
+    fn test_translated_day_2_matches_interpreter() {
+        let base = MemoryBank::from_file(day_input_path(2)).unwrap();
+        for (noun, verb) in [(12, 2), (0, 0), (59, 36), (99, 99)] {
+            let mut memory = base.clone();
+            memory.set_scalar_at(1, noun);
+            memory.set_scalar_at(2, verb);
+            let mut vm = VirtualMachine::from_memory(memory.clone());
+            vm.run_until_halt().unwrap();
+
+            let translated = translated_day_2::run(memory, QueueDevice::new(), vec![]).unwrap();
+            assert_eq!(&translated, vm.memory_snapshot());
+        }
+    }
+
+    #[test]
+    fn test_translated_day_5_matches_interpreter() {
+        let tape = MemoryBank::from_file(day_input_path(5)).unwrap().raw();
+        // 0 turns the word patched by the first input into an unknown opcode
+        for input in [1, 5, 8, 0] {
+            let mut vm =
+                VirtualMachine::from_tape(&tape).with_input(QueueDevice::from(vec![input]));
+            let expected = vm.run_until_halt();
+
+            let output = QueueDevice::new();
+            let translated = translated_day_5::run(
+                MemoryBank::new(tape.clone()),
+                QueueDevice::from(vec![input]),
+                output.clone(),
+            );
+            match expected {
+                Ok(outputs) => {
+                    assert_eq!(&translated.unwrap(), vm.memory_snapshot());
+                    assert_eq!(output.drain(), outputs);
+                }
+                Err(error) => assert_eq!(translated, Err(error)),
+            }
+        }
+    }
+}
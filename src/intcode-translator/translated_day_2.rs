@@ -0,0 +1,284 @@
+//! Generated by `intcode-translator` from a 165-word tape: do not edit.
+
+use advent_2019_common::intcode::{
+    InputDevice, InstructionMode::*, IntcodeResult, MemoryBank, OutputDevice,
+    TranslatedRuntime,
+};
+
+/// Addresses of the translated instruction words.
+const CODE: [usize; 42] = [
+    0, 4, 8, 12, 16, 20, 24, 28, 32, 36, 40, 44,
+    48, 52, 56, 60, 64, 68, 72, 76, 80, 84, 88, 92,
+    96, 100, 104, 108, 112, 116, 120, 124, 128, 132, 136, 140,
+    144, 148, 152, 156, 160, 161,
+];
+
+pub fn run(
+    memory: MemoryBank,
+    input: impl InputDevice,
+    output: impl OutputDevice,
+) -> IntcodeResult<MemoryBank> {
+    let mut rt = TranslatedRuntime::new(memory, &CODE, input, output);
+    let mut pc = 0;
+    loop {
+        pc = match pc {
+            _ if rt.is_patched(pc) => match rt.interpret(pc)? {
+                Some(next) => next,
+                None => break,
+            },
+            // ADD 0, 0, 3
+            0 => {
+                let value = rt.add(0, rt.read(0, 0, Position)?, rt.read(0, 1, Position)?)?;
+                rt.write(0, 2, Position, value)?;
+                4
+            }
+            // ADD 1, 2, 3
+            4 => {
+                let value = rt.add(4, rt.read(4, 0, Position)?, rt.read(4, 1, Position)?)?;
+                rt.write(4, 2, Position, value)?;
+                8
+            }
+            // ADD 3, 4, 3
+            8 => {
+                let value = rt.add(8, rt.read(8, 0, Position)?, rt.read(8, 1, Position)?)?;
+                rt.write(8, 2, Position, value)?;
+                12
+            }
+            // ADD 5, 0, 3
+            12 => {
+                let value = rt.add(12, rt.read(12, 0, Position)?, rt.read(12, 1, Position)?)?;
+                rt.write(12, 2, Position, value)?;
+                16
+            }
+            // MUL 10, 1, 19
+            16 => {
+                let value = rt.multiply(16, rt.read(16, 0, Position)?, rt.read(16, 1, Position)?)?;
+                rt.write(16, 2, Position, value)?;
+                20
+            }
+            // ADD 19, 9, 23
+            20 => {
+                let value = rt.add(20, rt.read(20, 0, Position)?, rt.read(20, 1, Position)?)?;
+                rt.write(20, 2, Position, value)?;
+                24
+            }
+            // ADD 23, 6, 27
+            24 => {
+                let value = rt.add(24, rt.read(24, 0, Position)?, rt.read(24, 1, Position)?)?;
+                rt.write(24, 2, Position, value)?;
+                28
+            }
+            // MUL 27, 13, 31
+            28 => {
+                let value = rt.multiply(28, rt.read(28, 0, Position)?, rt.read(28, 1, Position)?)?;
+                rt.write(28, 2, Position, value)?;
+                32
+            }
+            // ADD 10, 31, 35
+            32 => {
+                let value = rt.add(32, rt.read(32, 0, Position)?, rt.read(32, 1, Position)?)?;
+                rt.write(32, 2, Position, value)?;
+                36
+            }
+            // ADD 10, 35, 39
+            36 => {
+                let value = rt.add(36, rt.read(36, 0, Position)?, rt.read(36, 1, Position)?)?;
+                rt.write(36, 2, Position, value)?;
+                40
+            }
+            // MUL 39, 6, 43
+            40 => {
+                let value = rt.multiply(40, rt.read(40, 0, Position)?, rt.read(40, 1, Position)?)?;
+                rt.write(40, 2, Position, value)?;
+                44
+            }
+            // ADD 43, 5, 47
+            44 => {
+                let value = rt.add(44, rt.read(44, 0, Position)?, rt.read(44, 1, Position)?)?;
+                rt.write(44, 2, Position, value)?;
+                48
+            }
+            // MUL 10, 47, 51
+            48 => {
+                let value = rt.multiply(48, rt.read(48, 0, Position)?, rt.read(48, 1, Position)?)?;
+                rt.write(48, 2, Position, value)?;
+                52
+            }
+            // ADD 5, 51, 55
+            52 => {
+                let value = rt.add(52, rt.read(52, 0, Position)?, rt.read(52, 1, Position)?)?;
+                rt.write(52, 2, Position, value)?;
+                56
+            }
+            // ADD 55, 13, 59
+            56 => {
+                let value = rt.add(56, rt.read(56, 0, Position)?, rt.read(56, 1, Position)?)?;
+                rt.write(56, 2, Position, value)?;
+                60
+            }
+            // ADD 59, 9, 63
+            60 => {
+                let value = rt.add(60, rt.read(60, 0, Position)?, rt.read(60, 1, Position)?)?;
+                rt.write(60, 2, Position, value)?;
+                64
+            }
+            // MUL 9, 63, 67
+            64 => {
+                let value = rt.multiply(64, rt.read(64, 0, Position)?, rt.read(64, 1, Position)?)?;
+                rt.write(64, 2, Position, value)?;
+                68
+            }
+            // ADD 6, 67, 71
+            68 => {
+                let value = rt.add(68, rt.read(68, 0, Position)?, rt.read(68, 1, Position)?)?;
+                rt.write(68, 2, Position, value)?;
+                72
+            }
+            // ADD 71, 13, 75
+            72 => {
+                let value = rt.add(72, rt.read(72, 0, Position)?, rt.read(72, 1, Position)?)?;
+                rt.write(72, 2, Position, value)?;
+                76
+            }
+            // ADD 75, 10, 79
+            76 => {
+                let value = rt.add(76, rt.read(76, 0, Position)?, rt.read(76, 1, Position)?)?;
+                rt.write(76, 2, Position, value)?;
+                80
+            }
+            // ADD 5, 79, 83
+            80 => {
+                let value = rt.add(80, rt.read(80, 0, Position)?, rt.read(80, 1, Position)?)?;
+                rt.write(80, 2, Position, value)?;
+                84
+            }
+            // ADD 10, 83, 87
+            84 => {
+                let value = rt.add(84, rt.read(84, 0, Position)?, rt.read(84, 1, Position)?)?;
+                rt.write(84, 2, Position, value)?;
+                88
+            }
+            // ADD 5, 87, 91
+            88 => {
+                let value = rt.add(88, rt.read(88, 0, Position)?, rt.read(88, 1, Position)?)?;
+                rt.write(88, 2, Position, value)?;
+                92
+            }
+            // ADD 91, 9, 95
+            92 => {
+                let value = rt.add(92, rt.read(92, 0, Position)?, rt.read(92, 1, Position)?)?;
+                rt.write(92, 2, Position, value)?;
+                96
+            }
+            // MUL 13, 95, 99
+            96 => {
+                let value = rt.multiply(96, rt.read(96, 0, Position)?, rt.read(96, 1, Position)?)?;
+                rt.write(96, 2, Position, value)?;
+                100
+            }
+            // ADD 5, 99, 103
+            100 => {
+                let value = rt.add(100, rt.read(100, 0, Position)?, rt.read(100, 1, Position)?)?;
+                rt.write(100, 2, Position, value)?;
+                104
+            }
+            // MUL 103, 9, 107
+            104 => {
+                let value = rt.multiply(104, rt.read(104, 0, Position)?, rt.read(104, 1, Position)?)?;
+                rt.write(104, 2, Position, value)?;
+                108
+            }
+            // ADD 5, 107, 111
+            108 => {
+                let value = rt.add(108, rt.read(108, 0, Position)?, rt.read(108, 1, Position)?)?;
+                rt.write(108, 2, Position, value)?;
+                112
+            }
+            // MUL 111, 9, 115
+            112 => {
+                let value = rt.multiply(112, rt.read(112, 0, Position)?, rt.read(112, 1, Position)?)?;
+                rt.write(112, 2, Position, value)?;
+                116
+            }
+            // ADD 115, 6, 119
+            116 => {
+                let value = rt.add(116, rt.read(116, 0, Position)?, rt.read(116, 1, Position)?)?;
+                rt.write(116, 2, Position, value)?;
+                120
+            }
+            // MUL 13, 119, 123
+            120 => {
+                let value = rt.multiply(120, rt.read(120, 0, Position)?, rt.read(120, 1, Position)?)?;
+                rt.write(120, 2, Position, value)?;
+                124
+            }
+            // ADD 123, 5, 127
+            124 => {
+                let value = rt.add(124, rt.read(124, 0, Position)?, rt.read(124, 1, Position)?)?;
+                rt.write(124, 2, Position, value)?;
+                128
+            }
+            // ADD 127, 9, 131
+            128 => {
+                let value = rt.add(128, rt.read(128, 0, Position)?, rt.read(128, 1, Position)?)?;
+                rt.write(128, 2, Position, value)?;
+                132
+            }
+            // ADD 131, 10, 135
+            132 => {
+                let value = rt.add(132, rt.read(132, 0, Position)?, rt.read(132, 1, Position)?)?;
+                rt.write(132, 2, Position, value)?;
+                136
+            }
+            // ADD 13, 135, 139
+            136 => {
+                let value = rt.add(136, rt.read(136, 0, Position)?, rt.read(136, 1, Position)?)?;
+                rt.write(136, 2, Position, value)?;
+                140
+            }
+            // MUL 9, 139, 143
+            140 => {
+                let value = rt.multiply(140, rt.read(140, 0, Position)?, rt.read(140, 1, Position)?)?;
+                rt.write(140, 2, Position, value)?;
+                144
+            }
+            // ADD 5, 143, 147
+            144 => {
+                let value = rt.add(144, rt.read(144, 0, Position)?, rt.read(144, 1, Position)?)?;
+                rt.write(144, 2, Position, value)?;
+                148
+            }
+            // ADD 13, 147, 151
+            148 => {
+                let value = rt.add(148, rt.read(148, 0, Position)?, rt.read(148, 1, Position)?)?;
+                rt.write(148, 2, Position, value)?;
+                152
+            }
+            // ADD 151, 2, 155
+            152 => {
+                let value = rt.add(152, rt.read(152, 0, Position)?, rt.read(152, 1, Position)?)?;
+                rt.write(152, 2, Position, value)?;
+                156
+            }
+            // ADD 10, 155, 0
+            156 => {
+                let value = rt.add(156, rt.read(156, 0, Position)?, rt.read(156, 1, Position)?)?;
+                rt.write(156, 2, Position, value)?;
+                160
+            }
+            // HLT
+            160 => break,
+            // MUL 14, 0, 0
+            161 => {
+                let value = rt.multiply(161, rt.read(161, 0, Position)?, rt.read(161, 1, Position)?)?;
+                rt.write(161, 2, Position, value)?;
+                165
+            }
+            _ => match rt.interpret(pc)? {
+                Some(next) => next,
+                None => break,
+            },
+        };
+    }
+    Ok(rt.halt())
+}
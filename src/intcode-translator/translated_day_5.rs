@@ -0,0 +1,1216 @@
+//! Generated by `intcode-translator` from a 678-word tape: do not edit.
+
+use advent_2019_common::intcode::{
+    InputDevice, InstructionMode::*, IntcodeResult, MemoryBank, OutputDevice, Scalar,
+    TranslatedRuntime,
+};
+
+/// Addresses of the translated instruction words.
+const CODE: [usize; 183] = [
+    0, 2, 7, 12, 16, 20, 22, 26, 30, 34, 38, 42,
+    46, 50, 54, 56, 60, 64, 68, 72, 76, 78, 82, 86,
+    90, 94, 98, 102, 104, 108, 112, 116, 120, 124, 126, 130,
+    134, 138, 142, 146, 150, 152, 156, 160, 164, 168, 172, 176,
+    180, 182, 186, 190, 194, 198, 202, 204, 208, 212, 216, 220,
+    222, 238, 241, 244, 247, 250, 253, 256, 259, 262, 265, 268,
+    271, 274, 277, 280, 284, 288, 291, 294, 297, 300, 304, 308,
+    311, 314, 318, 322, 325, 329, 333, 337, 340, 344, 348, 352,
+    355, 359, 363, 367, 370, 374, 378, 382, 385, 389, 393, 397,
+    400, 404, 408, 412, 415, 419, 423, 427, 430, 434, 438, 442,
+    445, 449, 453, 457, 460, 464, 468, 472, 475, 479, 483, 487,
+    490, 494, 498, 502, 505, 509, 513, 517, 520, 524, 528, 532,
+    535, 539, 543, 547, 550, 554, 558, 562, 565, 569, 573, 577,
+    580, 584, 588, 592, 595, 599, 603, 607, 610, 614, 618, 622,
+    625, 629, 633, 637, 640, 644, 648, 652, 655, 659, 663, 667,
+    670, 674, 676,
+];
+
+pub fn run(
+    memory: MemoryBank,
+    input: impl InputDevice,
+    output: impl OutputDevice,
+) -> IntcodeResult<MemoryBank> {
+    let mut rt = TranslatedRuntime::new(memory, &CODE, input, output);
+    let mut pc = 0;
+    loop {
+        pc = match pc {
+            _ if rt.is_patched(pc) => match rt.interpret(pc)? {
+                Some(next) => next,
+                None => break,
+            },
+            // IN 225
+            0 => {
+                let value = rt.input(0)?;
+                rt.write(0, 0, Position, value)?;
+                2
+            }
+            // ADD 225, 6, 6
+            2 => {
+                let value = rt.add(2, rt.read(2, 0, Position)?, rt.read(2, 1, Position)?)?;
+                rt.write(2, 2, Position, value)?;
+                6
+            }
+            // ADD 238, 225, 104
+            7 => {
+                let value = rt.add(7, rt.read(7, 0, Position)?, rt.read(7, 1, Position)?)?;
+                rt.write(7, 2, Position, value)?;
+                11
+            }
+            // ADD #40, #27, 224
+            12 => {
+                let value = rt.add(12, rt.read(12, 0, Immediate)?, rt.read(12, 1, Immediate)?)?;
+                rt.write(12, 2, Position, value)?;
+                16
+            }
+            // ADD #-67, 224, 224
+            16 => {
+                let value = rt.add(16, rt.read(16, 0, Immediate)?, rt.read(16, 1, Position)?)?;
+                rt.write(16, 2, Position, value)?;
+                20
+            }
+            // OUT 224
+            20 => {
+                rt.output(rt.read(20, 0, Position)?);
+                22
+            }
+            // MUL 223, #8, 223
+            22 => {
+                let value = rt.multiply(22, rt.read(22, 0, Position)?, rt.read(22, 1, Immediate)?)?;
+                rt.write(22, 2, Position, value)?;
+                26
+            }
+            // ADD 224, #2, 224
+            26 => {
+                let value = rt.add(26, rt.read(26, 0, Position)?, rt.read(26, 1, Immediate)?)?;
+                rt.write(26, 2, Position, value)?;
+                30
+            }
+            // ADD 224, 223, 223
+            30 => {
+                let value = rt.add(30, rt.read(30, 0, Position)?, rt.read(30, 1, Position)?)?;
+                rt.write(30, 2, Position, value)?;
+                34
+            }
+            // ADD #33, #38, 225
+            34 => {
+                let value = rt.add(34, rt.read(34, 0, Immediate)?, rt.read(34, 1, Immediate)?)?;
+                rt.write(34, 2, Position, value)?;
+                38
+            }
+            // MUL #84, #60, 225
+            38 => {
+                let value = rt.multiply(38, rt.read(38, 0, Immediate)?, rt.read(38, 1, Immediate)?)?;
+                rt.write(38, 2, Position, value)?;
+                42
+            }
+            // ADD #65, #62, 225
+            42 => {
+                let value = rt.add(42, rt.read(42, 0, Immediate)?, rt.read(42, 1, Immediate)?)?;
+                rt.write(42, 2, Position, value)?;
+                46
+            }
+            // MUL 36, #13, 224
+            46 => {
+                let value = rt.multiply(46, rt.read(46, 0, Position)?, rt.read(46, 1, Immediate)?)?;
+                rt.write(46, 2, Position, value)?;
+                50
+            }
+            // ADD 224, #-494, 224
+            50 => {
+                let value = rt.add(50, rt.read(50, 0, Position)?, rt.read(50, 1, Immediate)?)?;
+                rt.write(50, 2, Position, value)?;
+                54
+            }
+            // OUT 224
+            54 => {
+                rt.output(rt.read(54, 0, Position)?);
+                56
+            }
+            // MUL 223, #8, 223
+            56 => {
+                let value = rt.multiply(56, rt.read(56, 0, Position)?, rt.read(56, 1, Immediate)?)?;
+                rt.write(56, 2, Position, value)?;
+                60
+            }
+            // ADD 224, #3, 224
+            60 => {
+                let value = rt.add(60, rt.read(60, 0, Position)?, rt.read(60, 1, Immediate)?)?;
+                rt.write(60, 2, Position, value)?;
+                64
+            }
+            // ADD 223, 224, 223
+            64 => {
+                let value = rt.add(64, rt.read(64, 0, Position)?, rt.read(64, 1, Position)?)?;
+                rt.write(64, 2, Position, value)?;
+                68
+            }
+            // MUL #86, #5, 224
+            68 => {
+                let value = rt.multiply(68, rt.read(68, 0, Immediate)?, rt.read(68, 1, Immediate)?)?;
+                rt.write(68, 2, Position, value)?;
+                72
+            }
+            // ADD #-430, 224, 224
+            72 => {
+                let value = rt.add(72, rt.read(72, 0, Immediate)?, rt.read(72, 1, Position)?)?;
+                rt.write(72, 2, Position, value)?;
+                76
+            }
+            // OUT 224
+            76 => {
+                rt.output(rt.read(76, 0, Position)?);
+                78
+            }
+            // MUL 223, #8, 223
+            78 => {
+                let value = rt.multiply(78, rt.read(78, 0, Position)?, rt.read(78, 1, Immediate)?)?;
+                rt.write(78, 2, Position, value)?;
+                82
+            }
+            // ADD #6, 224, 224
+            82 => {
+                let value = rt.add(82, rt.read(82, 0, Immediate)?, rt.read(82, 1, Position)?)?;
+                rt.write(82, 2, Position, value)?;
+                86
+            }
+            // ADD 223, 224, 223
+            86 => {
+                let value = rt.add(86, rt.read(86, 0, Position)?, rt.read(86, 1, Position)?)?;
+                rt.write(86, 2, Position, value)?;
+                90
+            }
+            // MUL #23, #50, 225
+            90 => {
+                let value = rt.multiply(90, rt.read(90, 0, Immediate)?, rt.read(90, 1, Immediate)?)?;
+                rt.write(90, 2, Position, value)?;
+                94
+            }
+            // ADD 44, #10, 224
+            94 => {
+                let value = rt.add(94, rt.read(94, 0, Position)?, rt.read(94, 1, Immediate)?)?;
+                rt.write(94, 2, Position, value)?;
+                98
+            }
+            // ADD #-72, 224, 224
+            98 => {
+                let value = rt.add(98, rt.read(98, 0, Immediate)?, rt.read(98, 1, Position)?)?;
+                rt.write(98, 2, Position, value)?;
+                102
+            }
+            // OUT 224
+            102 => {
+                rt.output(rt.read(102, 0, Position)?);
+                104
+            }
+            // MUL #8, 223, 223
+            104 => {
+                let value = rt.multiply(104, rt.read(104, 0, Immediate)?, rt.read(104, 1, Position)?)?;
+                rt.write(104, 2, Position, value)?;
+                108
+            }
+            // ADD #1, 224, 224
+            108 => {
+                let value = rt.add(108, rt.read(108, 0, Immediate)?, rt.read(108, 1, Position)?)?;
+                rt.write(108, 2, Position, value)?;
+                112
+            }
+            // ADD 224, 223, 223
+            112 => {
+                let value = rt.add(112, rt.read(112, 0, Position)?, rt.read(112, 1, Position)?)?;
+                rt.write(112, 2, Position, value)?;
+                116
+            }
+            // MUL #47, 217, 224
+            116 => {
+                let value = rt.multiply(116, rt.read(116, 0, Immediate)?, rt.read(116, 1, Position)?)?;
+                rt.write(116, 2, Position, value)?;
+                120
+            }
+            // ADD 224, #-2303, 224
+            120 => {
+                let value = rt.add(120, rt.read(120, 0, Position)?, rt.read(120, 1, Immediate)?)?;
+                rt.write(120, 2, Position, value)?;
+                124
+            }
+            // OUT 224
+            124 => {
+                rt.output(rt.read(124, 0, Position)?);
+                126
+            }
+            // MUL #8, 223, 223
+            126 => {
+                let value = rt.multiply(126, rt.read(126, 0, Immediate)?, rt.read(126, 1, Position)?)?;
+                rt.write(126, 2, Position, value)?;
+                130
+            }
+            // ADD #2, 224, 224
+            130 => {
+                let value = rt.add(130, rt.read(130, 0, Immediate)?, rt.read(130, 1, Position)?)?;
+                rt.write(130, 2, Position, value)?;
+                134
+            }
+            // ADD 223, 224, 223
+            134 => {
+                let value = rt.add(134, rt.read(134, 0, Position)?, rt.read(134, 1, Position)?)?;
+                rt.write(134, 2, Position, value)?;
+                138
+            }
+            // MUL #71, #84, 225
+            138 => {
+                let value = rt.multiply(138, rt.read(138, 0, Immediate)?, rt.read(138, 1, Immediate)?)?;
+                rt.write(138, 2, Position, value)?;
+                142
+            }
+            // ADD #91, 40, 224
+            142 => {
+                let value = rt.add(142, rt.read(142, 0, Immediate)?, rt.read(142, 1, Position)?)?;
+                rt.write(142, 2, Position, value)?;
+                146
+            }
+            // ADD 224, #-151, 224
+            146 => {
+                let value = rt.add(146, rt.read(146, 0, Position)?, rt.read(146, 1, Immediate)?)?;
+                rt.write(146, 2, Position, value)?;
+                150
+            }
+            // OUT 224
+            150 => {
+                rt.output(rt.read(150, 0, Position)?);
+                152
+            }
+            // MUL 223, #8, 223
+            152 => {
+                let value = rt.multiply(152, rt.read(152, 0, Position)?, rt.read(152, 1, Immediate)?)?;
+                rt.write(152, 2, Position, value)?;
+                156
+            }
+            // ADD 224, #5, 224
+            156 => {
+                let value = rt.add(156, rt.read(156, 0, Position)?, rt.read(156, 1, Immediate)?)?;
+                rt.write(156, 2, Position, value)?;
+                160
+            }
+            // ADD 223, 224, 223
+            160 => {
+                let value = rt.add(160, rt.read(160, 0, Position)?, rt.read(160, 1, Position)?)?;
+                rt.write(160, 2, Position, value)?;
+                164
+            }
+            // ADD #87, #91, 225
+            164 => {
+                let value = rt.add(164, rt.read(164, 0, Immediate)?, rt.read(164, 1, Immediate)?)?;
+                rt.write(164, 2, Position, value)?;
+                168
+            }
+            // MUL #71, #19, 225
+            168 => {
+                let value = rt.multiply(168, rt.read(168, 0, Immediate)?, rt.read(168, 1, Immediate)?)?;
+                rt.write(168, 2, Position, value)?;
+                172
+            }
+            // ADD 92, 140, 224
+            172 => {
+                let value = rt.add(172, rt.read(172, 0, Position)?, rt.read(172, 1, Position)?)?;
+                rt.write(172, 2, Position, value)?;
+                176
+            }
+            // ADD #-134, 224, 224
+            176 => {
+                let value = rt.add(176, rt.read(176, 0, Immediate)?, rt.read(176, 1, Position)?)?;
+                rt.write(176, 2, Position, value)?;
+                180
+            }
+            // OUT 224
+            180 => {
+                rt.output(rt.read(180, 0, Position)?);
+                182
+            }
+            // MUL 223, #8, 223
+            182 => {
+                let value = rt.multiply(182, rt.read(182, 0, Position)?, rt.read(182, 1, Immediate)?)?;
+                rt.write(182, 2, Position, value)?;
+                186
+            }
+            // ADD #1, 224, 224
+            186 => {
+                let value = rt.add(186, rt.read(186, 0, Immediate)?, rt.read(186, 1, Position)?)?;
+                rt.write(186, 2, Position, value)?;
+                190
+            }
+            // ADD 224, 223, 223
+            190 => {
+                let value = rt.add(190, rt.read(190, 0, Position)?, rt.read(190, 1, Position)?)?;
+                rt.write(190, 2, Position, value)?;
+                194
+            }
+            // MUL 170, 165, 224
+            194 => {
+                let value = rt.multiply(194, rt.read(194, 0, Position)?, rt.read(194, 1, Position)?)?;
+                rt.write(194, 2, Position, value)?;
+                198
+            }
+            // ADD 224, #-1653, 224
+            198 => {
+                let value = rt.add(198, rt.read(198, 0, Position)?, rt.read(198, 1, Immediate)?)?;
+                rt.write(198, 2, Position, value)?;
+                202
+            }
+            // OUT 224
+            202 => {
+                rt.output(rt.read(202, 0, Position)?);
+                204
+            }
+            // MUL 223, #8, 223
+            204 => {
+                let value = rt.multiply(204, rt.read(204, 0, Position)?, rt.read(204, 1, Immediate)?)?;
+                rt.write(204, 2, Position, value)?;
+                208
+            }
+            // ADD #5, 224, 224
+            208 => {
+                let value = rt.add(208, rt.read(208, 0, Immediate)?, rt.read(208, 1, Position)?)?;
+                rt.write(208, 2, Position, value)?;
+                212
+            }
+            // ADD 223, 224, 223
+            212 => {
+                let value = rt.add(212, rt.read(212, 0, Position)?, rt.read(212, 1, Position)?)?;
+                rt.write(212, 2, Position, value)?;
+                216
+            }
+            // ADD #49, #32, 225
+            216 => {
+                let value = rt.add(216, rt.read(216, 0, Immediate)?, rt.read(216, 1, Immediate)?)?;
+                rt.write(216, 2, Position, value)?;
+                220
+            }
+            // OUT 223
+            220 => {
+                rt.output(rt.read(220, 0, Position)?);
+                222
+            }
+            // HLT
+            222 => break,
+            // JT #0, #99999
+            238 => {
+                if rt.read(238, 0, Immediate)? != 0 {
+                    rt.jump_target(238, rt.read(238, 1, Immediate)?)?
+                } else {
+                    241
+                }
+            }
+            // JT #227, #247
+            241 => {
+                if rt.read(241, 0, Immediate)? != 0 {
+                    rt.jump_target(241, rt.read(241, 1, Immediate)?)?
+                } else {
+                    244
+                }
+            }
+            // JT #1, #99999
+            244 => {
+                if rt.read(244, 0, Immediate)? != 0 {
+                    rt.jump_target(244, rt.read(244, 1, Immediate)?)?
+                } else {
+                    247
+                }
+            }
+            // JT 227, #99999
+            247 => {
+                if rt.read(247, 0, Position)? != 0 {
+                    rt.jump_target(247, rt.read(247, 1, Immediate)?)?
+                } else {
+                    250
+                }
+            }
+            // JT 0, #256
+            250 => {
+                if rt.read(250, 0, Position)? != 0 {
+                    rt.jump_target(250, rt.read(250, 1, Immediate)?)?
+                } else {
+                    253
+                }
+            }
+            // JT #1, #99999
+            253 => {
+                if rt.read(253, 0, Immediate)? != 0 {
+                    rt.jump_target(253, rt.read(253, 1, Immediate)?)?
+                } else {
+                    256
+                }
+            }
+            // JF #227, #99999
+            256 => {
+                if rt.read(256, 0, Immediate)? == 0 {
+                    rt.jump_target(256, rt.read(256, 1, Immediate)?)?
+                } else {
+                    259
+                }
+            }
+            // JF #0, #265
+            259 => {
+                if rt.read(259, 0, Immediate)? == 0 {
+                    rt.jump_target(259, rt.read(259, 1, Immediate)?)?
+                } else {
+                    262
+                }
+            }
+            // JT #1, #99999
+            262 => {
+                if rt.read(262, 0, Immediate)? != 0 {
+                    rt.jump_target(262, rt.read(262, 1, Immediate)?)?
+                } else {
+                    265
+                }
+            }
+            // JF 0, #99999
+            265 => {
+                if rt.read(265, 0, Position)? == 0 {
+                    rt.jump_target(265, rt.read(265, 1, Immediate)?)?
+                } else {
+                    268
+                }
+            }
+            // JF 227, #274
+            268 => {
+                if rt.read(268, 0, Position)? == 0 {
+                    rt.jump_target(268, rt.read(268, 1, Immediate)?)?
+                } else {
+                    271
+                }
+            }
+            // JT #1, #99999
+            271 => {
+                if rt.read(271, 0, Immediate)? != 0 {
+                    rt.jump_target(271, rt.read(271, 1, Immediate)?)?
+                } else {
+                    274
+                }
+            }
+            // JT #1, #280
+            274 => {
+                if rt.read(274, 0, Immediate)? != 0 {
+                    rt.jump_target(274, rt.read(274, 1, Immediate)?)?
+                } else {
+                    277
+                }
+            }
+            // JT #1, #99999
+            277 => {
+                if rt.read(277, 0, Immediate)? != 0 {
+                    rt.jump_target(277, rt.read(277, 1, Immediate)?)?
+                } else {
+                    280
+                }
+            }
+            // ADD 225, 225, 225
+            280 => {
+                let value = rt.add(280, rt.read(280, 0, Position)?, rt.read(280, 1, Position)?)?;
+                rt.write(280, 2, Position, value)?;
+                284
+            }
+            // ADD #294, #0, 0
+            284 => {
+                let value = rt.add(284, rt.read(284, 0, Immediate)?, rt.read(284, 1, Immediate)?)?;
+                rt.write(284, 2, Position, value)?;
+                288
+            }
+            // JT #1, 0
+            288 => {
+                if rt.read(288, 0, Immediate)? != 0 {
+                    rt.jump_target(288, rt.read(288, 1, Position)?)?
+                } else {
+                    291
+                }
+            }
+            // JT #1, #99999
+            291 => {
+                if rt.read(291, 0, Immediate)? != 0 {
+                    rt.jump_target(291, rt.read(291, 1, Immediate)?)?
+                } else {
+                    294
+                }
+            }
+            // JF #0, #300
+            294 => {
+                if rt.read(294, 0, Immediate)? == 0 {
+                    rt.jump_target(294, rt.read(294, 1, Immediate)?)?
+                } else {
+                    297
+                }
+            }
+            // JT #1, #99999
+            297 => {
+                if rt.read(297, 0, Immediate)? != 0 {
+                    rt.jump_target(297, rt.read(297, 1, Immediate)?)?
+                } else {
+                    300
+                }
+            }
+            // ADD 225, 225, 225
+            300 => {
+                let value = rt.add(300, rt.read(300, 0, Position)?, rt.read(300, 1, Position)?)?;
+                rt.write(300, 2, Position, value)?;
+                304
+            }
+            // ADD #314, #0, 0
+            304 => {
+                let value = rt.add(304, rt.read(304, 0, Immediate)?, rt.read(304, 1, Immediate)?)?;
+                rt.write(304, 2, Position, value)?;
+                308
+            }
+            // JF #0, 0
+            308 => {
+                if rt.read(308, 0, Immediate)? == 0 {
+                    rt.jump_target(308, rt.read(308, 1, Position)?)?
+                } else {
+                    311
+                }
+            }
+            // JT #1, #99999
+            311 => {
+                if rt.read(311, 0, Immediate)? != 0 {
+                    rt.jump_target(311, rt.read(311, 1, Immediate)?)?
+                } else {
+                    314
+                }
+            }
+            // LT #226, #677, 224
+            314 => {
+                let value = Scalar::from(rt.read(314, 0, Immediate)? < rt.read(314, 1, Immediate)?);
+                rt.write(314, 2, Position, value)?;
+                318
+            }
+            // MUL 223, #2, 223
+            318 => {
+                let value = rt.multiply(318, rt.read(318, 0, Position)?, rt.read(318, 1, Immediate)?)?;
+                rt.write(318, 2, Position, value)?;
+                322
+            }
+            // JF 224, #329
+            322 => {
+                if rt.read(322, 0, Position)? == 0 {
+                    rt.jump_target(322, rt.read(322, 1, Immediate)?)?
+                } else {
+                    325
+                }
+            }
+            // ADD #1, 223, 223
+            325 => {
+                let value = rt.add(325, rt.read(325, 0, Immediate)?, rt.read(325, 1, Position)?)?;
+                rt.write(325, 2, Position, value)?;
+                329
+            }
+            // EQ 226, 226, 224
+            329 => {
+                let value = Scalar::from(rt.read(329, 0, Position)? == rt.read(329, 1, Position)?);
+                rt.write(329, 2, Position, value)?;
+                333
+            }
+            // MUL 223, #2, 223
+            333 => {
+                let value = rt.multiply(333, rt.read(333, 0, Position)?, rt.read(333, 1, Immediate)?)?;
+                rt.write(333, 2, Position, value)?;
+                337
+            }
+            // JT 224, #344
+            337 => {
+                if rt.read(337, 0, Position)? != 0 {
+                    rt.jump_target(337, rt.read(337, 1, Immediate)?)?
+                } else {
+                    340
+                }
+            }
+            // ADD #1, 223, 223
+            340 => {
+                let value = rt.add(340, rt.read(340, 0, Immediate)?, rt.read(340, 1, Position)?)?;
+                rt.write(340, 2, Position, value)?;
+                344
+            }
+            // LT 677, #226, 224
+            344 => {
+                let value = Scalar::from(rt.read(344, 0, Position)? < rt.read(344, 1, Immediate)?);
+                rt.write(344, 2, Position, value)?;
+                348
+            }
+            // MUL #2, 223, 223
+            348 => {
+                let value = rt.multiply(348, rt.read(348, 0, Immediate)?, rt.read(348, 1, Position)?)?;
+                rt.write(348, 2, Position, value)?;
+                352
+            }
+            // JT 224, #359
+            352 => {
+                if rt.read(352, 0, Position)? != 0 {
+                    rt.jump_target(352, rt.read(352, 1, Immediate)?)?
+                } else {
+                    355
+                }
+            }
+            // ADD #1, 223, 223
+            355 => {
+                let value = rt.add(355, rt.read(355, 0, Immediate)?, rt.read(355, 1, Position)?)?;
+                rt.write(355, 2, Position, value)?;
+                359
+            }
+            // EQ 226, 677, 224
+            359 => {
+                let value = Scalar::from(rt.read(359, 0, Position)? == rt.read(359, 1, Position)?);
+                rt.write(359, 2, Position, value)?;
+                363
+            }
+            // MUL #2, 223, 223
+            363 => {
+                let value = rt.multiply(363, rt.read(363, 0, Immediate)?, rt.read(363, 1, Position)?)?;
+                rt.write(363, 2, Position, value)?;
+                367
+            }
+            // JT 224, #374
+            367 => {
+                if rt.read(367, 0, Position)? != 0 {
+                    rt.jump_target(367, rt.read(367, 1, Immediate)?)?
+                } else {
+                    370
+                }
+            }
+            // ADD #1, 223, 223
+            370 => {
+                let value = rt.add(370, rt.read(370, 0, Immediate)?, rt.read(370, 1, Position)?)?;
+                rt.write(370, 2, Position, value)?;
+                374
+            }
+            // LT #677, #677, 224
+            374 => {
+                let value = Scalar::from(rt.read(374, 0, Immediate)? < rt.read(374, 1, Immediate)?);
+                rt.write(374, 2, Position, value)?;
+                378
+            }
+            // MUL 223, #2, 223
+            378 => {
+                let value = rt.multiply(378, rt.read(378, 0, Position)?, rt.read(378, 1, Immediate)?)?;
+                rt.write(378, 2, Position, value)?;
+                382
+            }
+            // JT 224, #389
+            382 => {
+                if rt.read(382, 0, Position)? != 0 {
+                    rt.jump_target(382, rt.read(382, 1, Immediate)?)?
+                } else {
+                    385
+                }
+            }
+            // ADD 223, #1, 223
+            385 => {
+                let value = rt.add(385, rt.read(385, 0, Position)?, rt.read(385, 1, Immediate)?)?;
+                rt.write(385, 2, Position, value)?;
+                389
+            }
+            // EQ #226, 677, 224
+            389 => {
+                let value = Scalar::from(rt.read(389, 0, Immediate)? == rt.read(389, 1, Position)?);
+                rt.write(389, 2, Position, value)?;
+                393
+            }
+            // MUL #2, 223, 223
+            393 => {
+                let value = rt.multiply(393, rt.read(393, 0, Immediate)?, rt.read(393, 1, Position)?)?;
+                rt.write(393, 2, Position, value)?;
+                397
+            }
+            // JT 224, #404
+            397 => {
+                if rt.read(397, 0, Position)? != 0 {
+                    rt.jump_target(397, rt.read(397, 1, Immediate)?)?
+                } else {
+                    400
+                }
+            }
+            // ADD 223, #1, 223
+            400 => {
+                let value = rt.add(400, rt.read(400, 0, Position)?, rt.read(400, 1, Immediate)?)?;
+                rt.write(400, 2, Position, value)?;
+                404
+            }
+            // EQ #677, 677, 224
+            404 => {
+                let value = Scalar::from(rt.read(404, 0, Immediate)? == rt.read(404, 1, Position)?);
+                rt.write(404, 2, Position, value)?;
+                408
+            }
+            // MUL 223, #2, 223
+            408 => {
+                let value = rt.multiply(408, rt.read(408, 0, Position)?, rt.read(408, 1, Immediate)?)?;
+                rt.write(408, 2, Position, value)?;
+                412
+            }
+            // JF 224, #419
+            412 => {
+                if rt.read(412, 0, Position)? == 0 {
+                    rt.jump_target(412, rt.read(412, 1, Immediate)?)?
+                } else {
+                    415
+                }
+            }
+            // ADD #1, 223, 223
+            415 => {
+                let value = rt.add(415, rt.read(415, 0, Immediate)?, rt.read(415, 1, Position)?)?;
+                rt.write(415, 2, Position, value)?;
+                419
+            }
+            // LT #677, 677, 224
+            419 => {
+                let value = Scalar::from(rt.read(419, 0, Immediate)? < rt.read(419, 1, Position)?);
+                rt.write(419, 2, Position, value)?;
+                423
+            }
+            // MUL #2, 223, 223
+            423 => {
+                let value = rt.multiply(423, rt.read(423, 0, Immediate)?, rt.read(423, 1, Position)?)?;
+                rt.write(423, 2, Position, value)?;
+                427
+            }
+            // JF 224, #434
+            427 => {
+                if rt.read(427, 0, Position)? == 0 {
+                    rt.jump_target(427, rt.read(427, 1, Immediate)?)?
+                } else {
+                    430
+                }
+            }
+            // ADD #1, 223, 223
+            430 => {
+                let value = rt.add(430, rt.read(430, 0, Immediate)?, rt.read(430, 1, Position)?)?;
+                rt.write(430, 2, Position, value)?;
+                434
+            }
+            // EQ #226, 226, 224
+            434 => {
+                let value = Scalar::from(rt.read(434, 0, Immediate)? == rt.read(434, 1, Position)?);
+                rt.write(434, 2, Position, value)?;
+                438
+            }
+            // MUL 223, #2, 223
+            438 => {
+                let value = rt.multiply(438, rt.read(438, 0, Position)?, rt.read(438, 1, Immediate)?)?;
+                rt.write(438, 2, Position, value)?;
+                442
+            }
+            // JF 224, #449
+            442 => {
+                if rt.read(442, 0, Position)? == 0 {
+                    rt.jump_target(442, rt.read(442, 1, Immediate)?)?
+                } else {
+                    445
+                }
+            }
+            // ADD 223, #1, 223
+            445 => {
+                let value = rt.add(445, rt.read(445, 0, Position)?, rt.read(445, 1, Immediate)?)?;
+                rt.write(445, 2, Position, value)?;
+                449
+            }
+            // EQ 677, 226, 224
+            449 => {
+                let value = Scalar::from(rt.read(449, 0, Position)? == rt.read(449, 1, Position)?);
+                rt.write(449, 2, Position, value)?;
+                453
+            }
+            // MUL 223, #2, 223
+            453 => {
+                let value = rt.multiply(453, rt.read(453, 0, Position)?, rt.read(453, 1, Immediate)?)?;
+                rt.write(453, 2, Position, value)?;
+                457
+            }
+            // JT 224, #464
+            457 => {
+                if rt.read(457, 0, Position)? != 0 {
+                    rt.jump_target(457, rt.read(457, 1, Immediate)?)?
+                } else {
+                    460
+                }
+            }
+            // ADD #1, 223, 223
+            460 => {
+                let value = rt.add(460, rt.read(460, 0, Immediate)?, rt.read(460, 1, Position)?)?;
+                rt.write(460, 2, Position, value)?;
+                464
+            }
+            // EQ #226, #677, 224
+            464 => {
+                let value = Scalar::from(rt.read(464, 0, Immediate)? == rt.read(464, 1, Immediate)?);
+                rt.write(464, 2, Position, value)?;
+                468
+            }
+            // MUL 223, #2, 223
+            468 => {
+                let value = rt.multiply(468, rt.read(468, 0, Position)?, rt.read(468, 1, Immediate)?)?;
+                rt.write(468, 2, Position, value)?;
+                472
+            }
+            // JF 224, #479
+            472 => {
+                if rt.read(472, 0, Position)? == 0 {
+                    rt.jump_target(472, rt.read(472, 1, Immediate)?)?
+                } else {
+                    475
+                }
+            }
+            // ADD 223, #1, 223
+            475 => {
+                let value = rt.add(475, rt.read(475, 0, Position)?, rt.read(475, 1, Immediate)?)?;
+                rt.write(475, 2, Position, value)?;
+                479
+            }
+            // EQ #677, #677, 224
+            479 => {
+                let value = Scalar::from(rt.read(479, 0, Immediate)? == rt.read(479, 1, Immediate)?);
+                rt.write(479, 2, Position, value)?;
+                483
+            }
+            // MUL 223, #2, 223
+            483 => {
+                let value = rt.multiply(483, rt.read(483, 0, Position)?, rt.read(483, 1, Immediate)?)?;
+                rt.write(483, 2, Position, value)?;
+                487
+            }
+            // JT 224, #494
+            487 => {
+                if rt.read(487, 0, Position)? != 0 {
+                    rt.jump_target(487, rt.read(487, 1, Immediate)?)?
+                } else {
+                    490
+                }
+            }
+            // ADD #1, 223, 223
+            490 => {
+                let value = rt.add(490, rt.read(490, 0, Immediate)?, rt.read(490, 1, Position)?)?;
+                rt.write(490, 2, Position, value)?;
+                494
+            }
+            // LT 677, 677, 224
+            494 => {
+                let value = Scalar::from(rt.read(494, 0, Position)? < rt.read(494, 1, Position)?);
+                rt.write(494, 2, Position, value)?;
+                498
+            }
+            // MUL 223, #2, 223
+            498 => {
+                let value = rt.multiply(498, rt.read(498, 0, Position)?, rt.read(498, 1, Immediate)?)?;
+                rt.write(498, 2, Position, value)?;
+                502
+            }
+            // JT 224, #509
+            502 => {
+                if rt.read(502, 0, Position)? != 0 {
+                    rt.jump_target(502, rt.read(502, 1, Immediate)?)?
+                } else {
+                    505
+                }
+            }
+            // ADD #1, 223, 223
+            505 => {
+                let value = rt.add(505, rt.read(505, 0, Immediate)?, rt.read(505, 1, Position)?)?;
+                rt.write(505, 2, Position, value)?;
+                509
+            }
+            // LT 677, #677, 224
+            509 => {
+                let value = Scalar::from(rt.read(509, 0, Position)? < rt.read(509, 1, Immediate)?);
+                rt.write(509, 2, Position, value)?;
+                513
+            }
+            // MUL 223, #2, 223
+            513 => {
+                let value = rt.multiply(513, rt.read(513, 0, Position)?, rt.read(513, 1, Immediate)?)?;
+                rt.write(513, 2, Position, value)?;
+                517
+            }
+            // JT 224, #524
+            517 => {
+                if rt.read(517, 0, Position)? != 0 {
+                    rt.jump_target(517, rt.read(517, 1, Immediate)?)?
+                } else {
+                    520
+                }
+            }
+            // ADD #1, 223, 223
+            520 => {
+                let value = rt.add(520, rt.read(520, 0, Immediate)?, rt.read(520, 1, Position)?)?;
+                rt.write(520, 2, Position, value)?;
+                524
+            }
+            // LT 677, 226, 224
+            524 => {
+                let value = Scalar::from(rt.read(524, 0, Position)? < rt.read(524, 1, Position)?);
+                rt.write(524, 2, Position, value)?;
+                528
+            }
+            // MUL 223, #2, 223
+            528 => {
+                let value = rt.multiply(528, rt.read(528, 0, Position)?, rt.read(528, 1, Immediate)?)?;
+                rt.write(528, 2, Position, value)?;
+                532
+            }
+            // JT 224, #539
+            532 => {
+                if rt.read(532, 0, Position)? != 0 {
+                    rt.jump_target(532, rt.read(532, 1, Immediate)?)?
+                } else {
+                    535
+                }
+            }
+            // ADD #1, 223, 223
+            535 => {
+                let value = rt.add(535, rt.read(535, 0, Immediate)?, rt.read(535, 1, Position)?)?;
+                rt.write(535, 2, Position, value)?;
+                539
+            }
+            // LT #677, #226, 224
+            539 => {
+                let value = Scalar::from(rt.read(539, 0, Immediate)? < rt.read(539, 1, Immediate)?);
+                rt.write(539, 2, Position, value)?;
+                543
+            }
+            // MUL #2, 223, 223
+            543 => {
+                let value = rt.multiply(543, rt.read(543, 0, Immediate)?, rt.read(543, 1, Position)?)?;
+                rt.write(543, 2, Position, value)?;
+                547
+            }
+            // JF 224, #554
+            547 => {
+                if rt.read(547, 0, Position)? == 0 {
+                    rt.jump_target(547, rt.read(547, 1, Immediate)?)?
+                } else {
+                    550
+                }
+            }
+            // ADD #1, 223, 223
+            550 => {
+                let value = rt.add(550, rt.read(550, 0, Immediate)?, rt.read(550, 1, Position)?)?;
+                rt.write(550, 2, Position, value)?;
+                554
+            }
+            // LT #226, 677, 224
+            554 => {
+                let value = Scalar::from(rt.read(554, 0, Immediate)? < rt.read(554, 1, Position)?);
+                rt.write(554, 2, Position, value)?;
+                558
+            }
+            // MUL 223, #2, 223
+            558 => {
+                let value = rt.multiply(558, rt.read(558, 0, Position)?, rt.read(558, 1, Immediate)?)?;
+                rt.write(558, 2, Position, value)?;
+                562
+            }
+            // JT 224, #569
+            562 => {
+                if rt.read(562, 0, Position)? != 0 {
+                    rt.jump_target(562, rt.read(562, 1, Immediate)?)?
+                } else {
+                    565
+                }
+            }
+            // ADD #1, 223, 223
+            565 => {
+                let value = rt.add(565, rt.read(565, 0, Immediate)?, rt.read(565, 1, Position)?)?;
+                rt.write(565, 2, Position, value)?;
+                569
+            }
+            // LT #226, 226, 224
+            569 => {
+                let value = Scalar::from(rt.read(569, 0, Immediate)? < rt.read(569, 1, Position)?);
+                rt.write(569, 2, Position, value)?;
+                573
+            }
+            // MUL 223, #2, 223
+            573 => {
+                let value = rt.multiply(573, rt.read(573, 0, Position)?, rt.read(573, 1, Immediate)?)?;
+                rt.write(573, 2, Position, value)?;
+                577
+            }
+            // JT 224, #584
+            577 => {
+                if rt.read(577, 0, Position)? != 0 {
+                    rt.jump_target(577, rt.read(577, 1, Immediate)?)?
+                } else {
+                    580
+                }
+            }
+            // ADD #1, 223, 223
+            580 => {
+                let value = rt.add(580, rt.read(580, 0, Immediate)?, rt.read(580, 1, Position)?)?;
+                rt.write(580, 2, Position, value)?;
+                584
+            }
+            // EQ #677, #226, 224
+            584 => {
+                let value = Scalar::from(rt.read(584, 0, Immediate)? == rt.read(584, 1, Immediate)?);
+                rt.write(584, 2, Position, value)?;
+                588
+            }
+            // MUL #2, 223, 223
+            588 => {
+                let value = rt.multiply(588, rt.read(588, 0, Immediate)?, rt.read(588, 1, Position)?)?;
+                rt.write(588, 2, Position, value)?;
+                592
+            }
+            // JF 224, #599
+            592 => {
+                if rt.read(592, 0, Position)? == 0 {
+                    rt.jump_target(592, rt.read(592, 1, Immediate)?)?
+                } else {
+                    595
+                }
+            }
+            // ADD 223, #1, 223
+            595 => {
+                let value = rt.add(595, rt.read(595, 0, Position)?, rt.read(595, 1, Immediate)?)?;
+                rt.write(595, 2, Position, value)?;
+                599
+            }
+            // EQ 677, #677, 224
+            599 => {
+                let value = Scalar::from(rt.read(599, 0, Position)? == rt.read(599, 1, Immediate)?);
+                rt.write(599, 2, Position, value)?;
+                603
+            }
+            // MUL #2, 223, 223
+            603 => {
+                let value = rt.multiply(603, rt.read(603, 0, Immediate)?, rt.read(603, 1, Position)?)?;
+                rt.write(603, 2, Position, value)?;
+                607
+            }
+            // JF 224, #614
+            607 => {
+                if rt.read(607, 0, Position)? == 0 {
+                    rt.jump_target(607, rt.read(607, 1, Immediate)?)?
+                } else {
+                    610
+                }
+            }
+            // ADD #1, 223, 223
+            610 => {
+                let value = rt.add(610, rt.read(610, 0, Immediate)?, rt.read(610, 1, Position)?)?;
+                rt.write(610, 2, Position, value)?;
+                614
+            }
+            // LT 226, 677, 224
+            614 => {
+                let value = Scalar::from(rt.read(614, 0, Position)? < rt.read(614, 1, Position)?);
+                rt.write(614, 2, Position, value)?;
+                618
+            }
+            // MUL #2, 223, 223
+            618 => {
+                let value = rt.multiply(618, rt.read(618, 0, Immediate)?, rt.read(618, 1, Position)?)?;
+                rt.write(618, 2, Position, value)?;
+                622
+            }
+            // JT 224, #629
+            622 => {
+                if rt.read(622, 0, Position)? != 0 {
+                    rt.jump_target(622, rt.read(622, 1, Immediate)?)?
+                } else {
+                    625
+                }
+            }
+            // ADD #1, 223, 223
+            625 => {
+                let value = rt.add(625, rt.read(625, 0, Immediate)?, rt.read(625, 1, Position)?)?;
+                rt.write(625, 2, Position, value)?;
+                629
+            }
+            // EQ 226, #677, 224
+            629 => {
+                let value = Scalar::from(rt.read(629, 0, Position)? == rt.read(629, 1, Immediate)?);
+                rt.write(629, 2, Position, value)?;
+                633
+            }
+            // MUL 223, #2, 223
+            633 => {
+                let value = rt.multiply(633, rt.read(633, 0, Position)?, rt.read(633, 1, Immediate)?)?;
+                rt.write(633, 2, Position, value)?;
+                637
+            }
+            // JF 224, #644
+            637 => {
+                if rt.read(637, 0, Position)? == 0 {
+                    rt.jump_target(637, rt.read(637, 1, Immediate)?)?
+                } else {
+                    640
+                }
+            }
+            // ADD #1, 223, 223
+            640 => {
+                let value = rt.add(640, rt.read(640, 0, Immediate)?, rt.read(640, 1, Position)?)?;
+                rt.write(640, 2, Position, value)?;
+                644
+            }
+            // LT 226, #226, 224
+            644 => {
+                let value = Scalar::from(rt.read(644, 0, Position)? < rt.read(644, 1, Immediate)?);
+                rt.write(644, 2, Position, value)?;
+                648
+            }
+            // MUL 223, #2, 223
+            648 => {
+                let value = rt.multiply(648, rt.read(648, 0, Position)?, rt.read(648, 1, Immediate)?)?;
+                rt.write(648, 2, Position, value)?;
+                652
+            }
+            // JT 224, #659
+            652 => {
+                if rt.read(652, 0, Position)? != 0 {
+                    rt.jump_target(652, rt.read(652, 1, Immediate)?)?
+                } else {
+                    655
+                }
+            }
+            // ADD 223, #1, 223
+            655 => {
+                let value = rt.add(655, rt.read(655, 0, Position)?, rt.read(655, 1, Immediate)?)?;
+                rt.write(655, 2, Position, value)?;
+                659
+            }
+            // EQ 226, #226, 224
+            659 => {
+                let value = Scalar::from(rt.read(659, 0, Position)? == rt.read(659, 1, Immediate)?);
+                rt.write(659, 2, Position, value)?;
+                663
+            }
+            // MUL #2, 223, 223
+            663 => {
+                let value = rt.multiply(663, rt.read(663, 0, Immediate)?, rt.read(663, 1, Position)?)?;
+                rt.write(663, 2, Position, value)?;
+                667
+            }
+            // JF 224, #674
+            667 => {
+                if rt.read(667, 0, Position)? == 0 {
+                    rt.jump_target(667, rt.read(667, 1, Immediate)?)?
+                } else {
+                    670
+                }
+            }
+            // ADD 223, #1, 223
+            670 => {
+                let value = rt.add(670, rt.read(670, 0, Position)?, rt.read(670, 1, Immediate)?)?;
+                rt.write(670, 2, Position, value)?;
+                674
+            }
+            // OUT 223
+            674 => {
+                rt.output(rt.read(674, 0, Position)?);
+                676
+            }
+            // HLT
+            676 => break,
+            _ => match rt.interpret(pc)? {
+                Some(next) => next,
+                None => break,
+            },
+        };
+    }
+    Ok(rt.halt())
+}
@@ -0,0 +1,344 @@
+//! Textual Intcode assembly.
+//!
+//! ```text
+//! ; comments run from `;` to the end of the line
+//! start:  add x, #3, x        ; bare operands are positions
+//!         out @-1             ; `#` marks immediate, `@` relative operands
+//!         jt #1, #start       ; labels resolve to their address
+//!         hlt
+//! x:      data 33, 0, x+1     ; raw words, `label+offset` is allowed too
+//! ```
+//!
+//! Mnemonics and directives are case-insensitive.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
+
+use super::{
+    disassembler::{disassemble, Disassembled},
+    instruction::{Instruction, InstructionMode, Parameter},
+    memory::{MemoryBank, Scalar},
+};
+
+const DATA_DIRECTIVE: &str = "DATA";
+
+/// Number of parameters of each mnemonic.
+const MNEMONICS: [(&str, usize); 10] = [
+    ("ADD", 3),
+    ("MUL", 3),
+    ("IN", 1),
+    ("OUT", 1),
+    ("JT", 2),
+    ("JF", 2),
+    ("LT", 3),
+    ("EQ", 3),
+    ("ARB", 1),
+    ("HLT", 0),
+];
+
+fn mnemonic_arity(mnemonic: &str) -> Option<usize> {
+    MNEMONICS
+        .iter()
+        .find(|(name, _)| *name == mnemonic)
+        .map(|(_, arity)| *arity)
+}
+
+#[derive(Debug)]
+enum Value {
+    Number(Scalar),
+    /// Structure: (label, offset)
+    Label(String, Scalar),
+}
+
+#[derive(Debug)]
+struct Operand {
+    mode: InstructionMode,
+    value: Value,
+}
+
+#[derive(Debug)]
+struct Statement {
+    line_number: usize,
+    /// Upper-cased mnemonic or directive.
+    operation: String,
+    operands: Vec<Operand>,
+}
+
+fn is_identifier(token: &str) -> bool {
+    let mut chars = token.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_value(raw: &str) -> Result<Value> {
+    if let Ok(number) = raw.parse() {
+        return Ok(Value::Number(number));
+    }
+    let (label, offset) = match raw.find(['+', '-']) {
+        Some(at) => {
+            let offset: Scalar = raw[at..]
+                .trim_start_matches('+')
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid label offset: {}", raw))?;
+            (raw[..at].trim(), offset)
+        }
+        None => (raw, 0),
+    };
+    if !is_identifier(label) {
+        return Err(anyhow!("invalid operand: {}", raw));
+    }
+    Ok(Value::Label(label.to_string(), offset))
+}
+
+fn parse_operand(raw: &str) -> Result<Operand> {
+    let (mode, value) = if let Some(value) = raw.strip_prefix('#') {
+        (InstructionMode::Immediate, value)
+    } else if let Some(value) = raw.strip_prefix('@') {
+        (InstructionMode::Relative, value)
+    } else {
+        (InstructionMode::Position, raw)
+    };
+    Ok(Operand {
+        mode,
+        value: parse_value(value.trim())?,
+    })
+}
+
+/// Splits the source into statements, recording label addresses.
+fn parse_source(source: &str) -> Result<(Vec<Statement>, HashMap<String, usize>)> {
+    let mut statements = vec![];
+    let mut labels = HashMap::new();
+    let mut address = 0;
+    for (i, raw_line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let mut line = raw_line.split(';').next().unwrap_or_default().trim();
+
+        if let Some((label, rest)) = line.split_once(':') {
+            let label = label.trim();
+            if !is_identifier(label) {
+                return Err(anyhow!(
+                    "assemble: line {}: invalid label: {}",
+                    line_number,
+                    label
+                ));
+            }
+            if labels.insert(label.to_string(), address).is_some() {
+                return Err(anyhow!(
+                    "assemble: line {}: duplicate label: {}",
+                    line_number,
+                    label
+                ));
+            }
+            line = rest.trim();
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let (operation, raw_operands) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let operation = operation.to_ascii_uppercase();
+        let operands = raw_operands
+            .split(',')
+            .map(str::trim)
+            .filter(|raw| !raw.is_empty())
+            .map(parse_operand)
+            .collect::<Result<Vec<Operand>>>()
+            .with_context(|| format!("assemble: line {}", line_number))?;
+
+        address += if operation == DATA_DIRECTIVE {
+            operands.len()
+        } else {
+            let arity = mnemonic_arity(&operation).ok_or_else(|| {
+                anyhow!(
+                    "assemble: line {}: unknown mnemonic: {}",
+                    line_number,
+                    operation
+                )
+            })?;
+            if operands.len() != arity {
+                return Err(anyhow!(
+                    "assemble: line {}: {} expects {} operands, got {}",
+                    line_number,
+                    operation,
+                    arity,
+                    operands.len()
+                ));
+            }
+            1 + arity
+        };
+        statements.push(Statement {
+            line_number,
+            operation,
+            operands,
+        });
+    }
+    Ok((statements, labels))
+}
+
+fn build_instruction(mnemonic: &str, parameters: &[Parameter]) -> Instruction {
+    match (mnemonic, parameters) {
+        ("ADD", &[a, b, c]) => Instruction::Add(a, b, c),
+        ("MUL", &[a, b, c]) => Instruction::Multiply(a, b, c),
+        ("IN", &[a]) => Instruction::Input(a),
+        ("OUT", &[a]) => Instruction::Output(a),
+        ("JT", &[a, b]) => Instruction::JumpIfTrue(a, b),
+        ("JF", &[a, b]) => Instruction::JumpIfFalse(a, b),
+        ("LT", &[a, b, c]) => Instruction::LessThan(a, b, c),
+        ("EQ", &[a, b, c]) => Instruction::Equals(a, b, c),
+        ("ARB", &[a]) => Instruction::AdjustRelativeBase(a),
+        ("HLT", &[]) => Instruction::Halt,
+        _ => unreachable!("arity is checked while parsing"),
+    }
+}
+
+/// Assembles Intcode source into a tape loadable by `VirtualMachine::from_tape`.
+pub fn assemble(source: &str) -> Result<Vec<Scalar>> {
+    let (statements, labels) = parse_source(source)?;
+
+    let mut tape = vec![];
+    for statement in statements {
+        let line_number = statement.line_number;
+        let resolve = |value: &Value| -> Result<Scalar> {
+            match value {
+                Value::Number(number) => Ok(*number),
+                Value::Label(label, offset) => labels
+                    .get(label)
+                    .map(|address| *address as Scalar + offset)
+                    .ok_or_else(|| {
+                        anyhow!("assemble: line {}: unknown label: {}", line_number, label)
+                    }),
+            }
+        };
+
+        if statement.operation == DATA_DIRECTIVE {
+            for operand in &statement.operands {
+                if operand.mode != InstructionMode::Position {
+                    return Err(anyhow!(
+                        "assemble: line {}: data words take no mode prefix",
+                        line_number
+                    ));
+                }
+                tape.push(resolve(&operand.value)?);
+            }
+            continue;
+        }
+
+        let mut parameters = Vec::with_capacity(statement.operands.len());
+        for operand in &statement.operands {
+            let value = resolve(&operand.value)?;
+            parameters.push(Parameter::new(operand.mode, value).ok_or_else(|| {
+                anyhow!(
+                    "assemble: line {}: negative address: {}",
+                    line_number,
+                    value
+                )
+            })?);
+        }
+        let instruction = build_instruction(&statement.operation, &parameters);
+        if let Some(Parameter::Immediate(_)) = instruction.target() {
+            return Err(anyhow!(
+                "assemble: line {}: {} cannot write to an immediate operand",
+                line_number,
+                statement.operation
+            ));
+        }
+        tape.extend(instruction.encode());
+    }
+    Ok(tape)
+}
+
+/// Prints a tape as assembly source that `assemble` turns back into the
+/// exact same tape.
+///
+/// Words whose decoding would not re-encode identically (e.g. unused mode
+/// digits) are kept as `DATA`.
+pub fn pretty_print(tape: &[Scalar]) -> String {
+    let memory = MemoryBank::new(tape.to_vec());
+    let mut source = String::new();
+    for line in disassemble(&memory) {
+        let statement = match line.content {
+            Disassembled::Instruction(instruction) if instruction.encode() == line.words => {
+                instruction.to_string()
+            }
+            _ => {
+                let words: Vec<String> = line.words.iter().map(|word| word.to_string()).collect();
+                format!("{} {}", DATA_DIRECTIVE, words.join(", "))
+            }
+        };
+        source.push_str(&format!("    {:<28}; {}\n", statement, line.address));
+    }
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assemble, pretty_print};
+
+    #[test]
+    fn test_assemble_day_2_example() {
+        let source = "
+            add lhs, rhs, 3     ; 1 + 9 + 10 + 3
+            mul 3, out, 0
+            hlt
+            lhs: data 30
+            rhs: data 40
+            out: data 50
+        ";
+        assert_eq!(
+            assemble(source).unwrap(),
+            vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]
+        );
+    }
+
+    #[test]
+    fn test_assemble_modes_and_labels() {
+        let source = "
+            start:
+                IN @-1
+                jt #1, #end
+                Out #-5
+            end: hlt
+            data end+1, end-1, -7
+        ";
+        assert_eq!(
+            assemble(source).unwrap(),
+            vec![203, -1, 1105, 1, 7, 104, -5, 99, 8, 6, -7]
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert!(assemble("foo 1").is_err());
+        assert!(assemble("add 1, 2").is_err());
+        assert!(assemble("add 1, 2, #3").is_err());
+        assert!(assemble("out missing").is_err());
+        assert!(assemble("a: hlt\na: hlt").is_err());
+        assert!(assemble("out -1").is_err());
+        assert!(assemble("data #1").is_err());
+        assert!(assemble("1abel: hlt").is_err());
+    }
+
+    #[test]
+    fn test_pretty_print_round_trip() {
+        for tape in [
+            vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50],
+            vec![
+                109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+            ],
+            // unused mode digits, negative and unknown words, truncated instruction
+            vec![1104, 5, 10099, -3, 42, 1, 2],
+        ] {
+            let source = pretty_print(&tape);
+            assert_eq!(assemble(&source).unwrap(), tape, "source:\n{}", source);
+        }
+        assert_eq!(
+            pretty_print(&[1002, 4, 3, 4, 33]),
+            concat!(
+                "    MUL 4, #3, 4                ; 0\n",
+                "    DATA 33                     ; 4\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,474 @@
+use std::{
+    collections::BTreeSet,
+    io::{BufRead, Write},
+};
+
+use anyhow::{anyhow, Context, Result};
+
+use super::{
+    error::IntcodeError,
+    io::QueueDevice,
+    memory::{scalar_to_address, Scalar},
+    vm::{RunStatus, VirtualMachine},
+};
+
+const DEBUGGER_PROMPT: &str = "(idb) ";
+
+/// Most memory words printed by a single `mem` command.
+const DEBUGGER_MAX_WORD_COUNT: usize = 1024;
+
+/// Most instructions `back` can undo, bounding the memory of the journal.
+const DEBUGGER_JOURNAL_LIMIT: usize = 100_000;
+
+const DEBUGGER_HELP: &str = "\
+commands:
+  step [n]            (s) execute n instructions (default 1)
+  back [n]                undo the last n instructions (default 1, up to 100000)
+  continue            (c) run until a breakpoint, watchpoint, halt or missing input
+  output              (o) like continue, but also stop after the next output
+  break <addr|op>     (b) break on an address or an opcode mnemonic (e.g. ADD)
+  unbreak <addr|op>       remove a breakpoint
+  watch <addr>        (w) stop after any write to an address
+  unwatch <addr>          remove a watchpoint
+  mem <addr> [count]  (x) print memory words
+  set <addr> <value>      patch a memory word
+  input <value>...    (i) queue program inputs
+  info                    print registers and breakpoints
+  help                    print this help
+  quit                (q) leave the debugger";
+
+/// Why execution stopped in the debugger.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebuggerStop {
+    /// The requested number of instructions was executed.
+    Stepped,
+    Breakpoint(usize),
+    OpcodeBreakpoint(usize, &'static str),
+    Watchpoint {
+        pc: usize,
+        address: usize,
+        old: Scalar,
+        new: Scalar,
+    },
+    Output(Scalar),
+    NeedsInput,
+    Halted,
+    Error(IntcodeError),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Resume {
+    Steps(usize),
+    Continue,
+    UntilOutput,
+}
+
+/// Interactive step debugger driving a single `VirtualMachine`.
+pub struct Debugger {
+    vm: VirtualMachine,
+    input: QueueDevice,
+    breakpoints: BTreeSet<usize>,
+    opcode_breakpoints: BTreeSet<String>,
+    watchpoints: BTreeSet<usize>,
+}
+
+fn parse_number<T: std::str::FromStr>(raw: Option<&str>, what: &str) -> Result<T> {
+    let raw = raw.ok_or_else(|| anyhow!("missing {}", what))?;
+    raw.parse()
+        .map_err(|_| anyhow!("invalid {}: {}", what, raw))
+}
+
+impl Debugger {
+    pub fn new(tape: &[Scalar]) -> Self {
+        let input = QueueDevice::new();
+        Self {
+            vm: VirtualMachine::from_tape(tape)
+                .with_input(input.clone())
+                .with_journal_limit(DEBUGGER_JOURNAL_LIMIT),
+            input,
+            breakpoints: BTreeSet::new(),
+            opcode_breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+        }
+    }
+
+    pub fn vm(&self) -> &VirtualMachine {
+        &self.vm
+    }
+
+    /// Renders the instruction at the program counter, e.g. `12: ADD 4, #3, 4`.
+    fn location(&self) -> String {
+        let pc = self.vm.program_counter_snapshot();
+        match self.vm.current_instruction() {
+            Ok(instruction) => format!("{}: {}", pc, instruction),
+            Err(error) => format!("{}: <{}>", pc, error),
+        }
+    }
+
+    /// Executes instructions until `resume` is satisfied or something stops
+    /// the machine. Outputs produced along the way are printed to `out`.
+    fn resume(&mut self, resume: Resume, out: &mut dyn Write) -> Result<DebuggerStop> {
+        let mut executed = 0;
+        loop {
+            if let Resume::Steps(steps) = resume {
+                if executed == steps {
+                    return Ok(DebuggerStop::Stepped);
+                }
+            }
+            let pc = self.vm.program_counter_snapshot();
+            let instruction = match self.vm.current_instruction() {
+                Ok(instruction) => instruction,
+                Err(error) => return Ok(DebuggerStop::Error(error)),
+            };
+            // never stop on the breakpoint execution is resumed from
+            if executed > 0 {
+                if self.breakpoints.contains(&pc) {
+                    return Ok(DebuggerStop::Breakpoint(pc));
+                }
+                if self.opcode_breakpoints.contains(instruction.mnemonic()) {
+                    return Ok(DebuggerStop::OpcodeBreakpoint(pc, instruction.mnemonic()));
+                }
+            }
+
+            let watched = instruction
+                .target()
+                .and_then(|target| target.address(self.vm.relative_base_snapshot()))
+                .and_then(scalar_to_address)
+                .filter(|address| self.watchpoints.contains(address))
+                .map(|address| (address, self.vm.memory_snapshot().get_scalar_at(address)));
+
+            let status = match self.vm.step() {
+                Ok(status) => status,
+                Err(error) => return Ok(DebuggerStop::Error(error)),
+            };
+            executed += 1;
+            match status {
+                Some(RunStatus::Halted) => return Ok(DebuggerStop::Halted),
+                Some(RunStatus::NeedsInput) => return Ok(DebuggerStop::NeedsInput),
+                Some(RunStatus::ProducedOutput(value)) => {
+                    writeln!(out, "output: {}", value)?;
+                    if resume == Resume::UntilOutput {
+                        return Ok(DebuggerStop::Output(value));
+                    }
+                }
+                None => {}
+            }
+            if let Some((address, old)) = watched {
+                return Ok(DebuggerStop::Watchpoint {
+                    pc,
+                    address,
+                    old,
+                    new: self.vm.memory_snapshot().get_scalar_at(address),
+                });
+            }
+        }
+    }
+
+    fn report_stop(&self, stop: DebuggerStop, out: &mut dyn Write) -> Result<()> {
+        match stop {
+            DebuggerStop::Stepped | DebuggerStop::Output(_) => {}
+            DebuggerStop::Breakpoint(pc) => writeln!(out, "breakpoint at {}", pc)?,
+            DebuggerStop::OpcodeBreakpoint(pc, mnemonic) => {
+                writeln!(out, "opcode breakpoint {} at {}", mnemonic, pc)?
+            }
+            DebuggerStop::Watchpoint {
+                pc,
+                address,
+                old,
+                new,
+            } => writeln!(
+                out,
+                "watchpoint {}: {} -> {} (written at {})",
+                address, old, new, pc
+            )?,
+            DebuggerStop::NeedsInput => writeln!(out, "waiting for input")?,
+            DebuggerStop::Halted => {
+                writeln!(out, "program halted")?;
+                return Ok(());
+            }
+            DebuggerStop::Error(error) => writeln!(out, "error: {}", error)?,
+        }
+        writeln!(out, "=> {}", self.location())?;
+        Ok(())
+    }
+
+    /// Executes a single command line.
+    ///
+    /// Returns false once the user asked to quit.
+    pub fn execute(&mut self, line: &str, out: &mut dyn Write) -> Result<bool> {
+        let mut tokens = line.split_whitespace();
+        let command = match tokens.next() {
+            Some(command) => command,
+            None => return Ok(true),
+        };
+        match command {
+            "step" | "s" => {
+                let steps = match tokens.next() {
+                    Some(raw) => parse_number(Some(raw), "step count")?,
+                    None => 1,
+                };
+                let stop = self.resume(Resume::Steps(steps), out)?;
+                self.report_stop(stop, out)?;
+            }
+            "back" => {
+                let steps: u64 = match tokens.next() {
+                    Some(raw) => parse_number(Some(raw), "step count")?,
+                    None => 1,
+                };
+                let cycle = self.vm.cycles().checked_sub(steps);
+                if !self.vm.rewind_to(cycle.unwrap_or(0)) || cycle.is_none() {
+                    if self.vm.cycles() == 0 {
+                        writeln!(out, "reached the start of the journal")?;
+                    } else {
+                        writeln!(
+                            out,
+                            "reached the journal limit of {} instructions",
+                            DEBUGGER_JOURNAL_LIMIT
+                        )?;
+                    }
+                }
+                writeln!(out, "=> {}", self.location())?;
+            }
+            "continue" | "c" => {
+                let stop = self.resume(Resume::Continue, out)?;
+                self.report_stop(stop, out)?;
+            }
+            "output" | "o" => {
+                let stop = self.resume(Resume::UntilOutput, out)?;
+                self.report_stop(stop, out)?;
+            }
+            "break" | "b" | "unbreak" => {
+                let target = tokens.next().ok_or_else(|| anyhow!("missing breakpoint"))?;
+                let set = command != "unbreak";
+                let description = match target.parse::<usize>() {
+                    Ok(address) => {
+                        if set {
+                            self.breakpoints.insert(address);
+                        } else {
+                            self.breakpoints.remove(&address);
+                        }
+                        format!("address {}", address)
+                    }
+                    Err(_) => {
+                        let mnemonic = target.to_ascii_uppercase();
+                        if set {
+                            self.opcode_breakpoints.insert(mnemonic.clone());
+                        } else {
+                            self.opcode_breakpoints.remove(&mnemonic);
+                        }
+                        format!("opcode {}", mnemonic)
+                    }
+                };
+                writeln!(
+                    out,
+                    "breakpoint {} {}",
+                    if set { "set on" } else { "removed from" },
+                    description
+                )?;
+            }
+            "watch" | "w" => {
+                let address = parse_number(tokens.next(), "address")?;
+                self.watchpoints.insert(address);
+                writeln!(out, "watchpoint set on {}", address)?;
+            }
+            "unwatch" => {
+                let address = parse_number(tokens.next(), "address")?;
+                self.watchpoints.remove(&address);
+                writeln!(out, "watchpoint removed from {}", address)?;
+            }
+            "mem" | "x" => {
+                let address: usize = parse_number(tokens.next(), "address")?;
+                let count = match tokens.next() {
+                    Some(raw) => parse_number(Some(raw), "word count")?,
+                    None => 1,
+                };
+                if count > DEBUGGER_MAX_WORD_COUNT {
+                    return Err(anyhow!(
+                        "word count above {}: {}",
+                        DEBUGGER_MAX_WORD_COUNT,
+                        count
+                    ));
+                }
+                let end = address
+                    .checked_add(count)
+                    .ok_or_else(|| anyhow!("address out of range: {}", address))?;
+                let memory = self.vm.memory_snapshot();
+                let words: Vec<String> = (address..end)
+                    .map(|at| memory.get_scalar_at(at).to_string())
+                    .collect();
+                writeln!(out, "{}: {}", address, words.join(" "))?;
+            }
+            "set" => {
+                let address = parse_number(tokens.next(), "address")?;
+                let value = parse_number(tokens.next(), "value")?;
+                self.vm.memory_mut().set_scalar_at(address, value);
+                writeln!(out, "{}: {}", address, value)?;
+            }
+            "input" | "i" => {
+                let mut count = 0;
+                for raw in tokens {
+                    self.input.push(parse_number(Some(raw), "input")?);
+                    count += 1;
+                }
+                writeln!(
+                    out,
+                    "{} input(s) queued, {} pending",
+                    count,
+                    self.input.len()
+                )?;
+            }
+            "info" => {
+                writeln!(
+                    out,
+                    "pc = {}, relative base = {}",
+                    self.vm.program_counter_snapshot(),
+                    self.vm.relative_base_snapshot()
+                )?;
+                writeln!(
+                    out,
+                    "breakpoints: {:?} {:?}",
+                    self.breakpoints, self.opcode_breakpoints
+                )?;
+                writeln!(out, "watchpoints: {:?}", self.watchpoints)?;
+                writeln!(out, "=> {}", self.location())?;
+            }
+            "help" | "h" => writeln!(out, "{}", DEBUGGER_HELP)?,
+            "quit" | "q" => return Ok(false),
+            _ => writeln!(out, "unknown command: {} (try help)", command)?,
+        }
+        Ok(true)
+    }
+
+    /// Reads and executes commands until `quit` or the end of `commands`.
+    ///
+    /// Command errors are printed and do not end the session.
+    pub fn repl<R: BufRead, W: Write>(
+        &mut self,
+        commands: R,
+        mut out: W,
+        prompt: bool,
+    ) -> Result<()> {
+        writeln!(out, "=> {}", self.location())?;
+        let mut lines = commands.lines();
+        loop {
+            if prompt {
+                write!(out, "{}", DEBUGGER_PROMPT)?;
+                out.flush()?;
+            }
+            let line = match lines.next() {
+                Some(line) => line.context("cannot read debugger command")?,
+                None => return Ok(()),
+            };
+            match self.execute(&line, &mut out) {
+                Ok(true) => {}
+                Ok(false) => return Ok(()),
+                Err(error) => writeln!(out, "error: {}", error)?,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::Debugger;
+    use crate::intcode::assemble;
+
+    fn run_script(tape: &[i64], script: &str) -> String {
+        let mut debugger = Debugger::new(tape);
+        let mut out = vec![];
+        debugger.repl(Cursor::new(script), &mut out, false).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_debugger_stepping_and_breakpoints() {
+        let tape = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        let transcript = run_script(
+            &tape,
+            "step\nmem 3\nbreak 8\ncontinue\nmem 0 1\nc\nback 2\nx 0\nback 5\nx 3\n",
+        );
+        assert_eq!(
+            transcript,
+            concat!(
+                "=> 0: ADD 9, 10, 3\n",
+                "=> 4: MUL 3, 11, 0\n",
+                "3: 70\n",
+                "breakpoint set on address 8\n",
+                "breakpoint at 8\n",
+                "=> 8: HLT\n",
+                "0: 3500\n",
+                "program halted\n",
+                "=> 4: MUL 3, 11, 0\n",
+                "0: 1\n",
+                "reached the start of the journal\n",
+                "=> 0: ADD 9, 10, 3\n",
+                "3: 3\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_debugger_opcode_breakpoints_and_watchpoints() {
+        let tape = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        let transcript = run_script(&tape, "b mul\nw 0\nc\nunbreak MUL\nc\nc\nquit\nc\n");
+        assert_eq!(
+            transcript,
+            concat!(
+                "=> 0: ADD 9, 10, 3\n",
+                "breakpoint set on opcode MUL\n",
+                "watchpoint set on 0\n",
+                "opcode breakpoint MUL at 4\n",
+                "=> 4: MUL 3, 11, 0\n",
+                "breakpoint removed from opcode MUL\n",
+                "watchpoint 0: 1 -> 3500 (written at 4)\n",
+                "=> 8: HLT\n",
+                "program halted\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_debugger_input_output_and_patching() {
+        let tape = assemble(
+            "
+            loop: in 100
+                  out 100
+                  jt #1, #loop
+        ",
+        )
+        .unwrap();
+        let transcript = run_script(
+            &tape,
+            "c\ni 4 5\no\nset 100 -1\nx 100\ninfo\nc\nstep 2\nfoo\nx\nx 18446744073709551615 2\nx 0 5000\nx 98 3\n",
+        );
+        assert_eq!(
+            transcript,
+            concat!(
+                "=> 0: IN 100\n",
+                "waiting for input\n",
+                "=> 0: IN 100\n",
+                "2 input(s) queued, 2 pending\n",
+                "output: 4\n",
+                "=> 4: JT #1, #0\n",
+                "100: -1\n",
+                "100: -1\n",
+                "pc = 4, relative base = 0\n",
+                "breakpoints: {} {}\n",
+                "watchpoints: {}\n",
+                "=> 4: JT #1, #0\n",
+                "output: 5\n",
+                "waiting for input\n",
+                "=> 0: IN 100\n",
+                "waiting for input\n",
+                "=> 0: IN 100\n",
+                "unknown command: foo (try help)\n",
+                "error: missing address\n",
+                "error: address out of range: 18446744073709551615\n",
+                "error: word count above 1024: 5000\n",
+                "98: 0 0 5\n",
+            )
+        );
+    }
+}
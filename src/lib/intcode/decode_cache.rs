@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use super::{instruction::Instruction, memory::MemoryBank};
+
+/// Widest instruction, in words.
+const MAX_INSTRUCTION_WIDTH: usize = 4;
+
+/// Instructions pre-decoded at every address of a tape.
+///
+/// The decoded instructions are shared between clones, so a single cache can
+/// serve every fork of a memory bank. Each clone keeps track of its own stale
+/// entries, whose words were written since the cache was built.
+#[derive(Clone, Debug)]
+pub struct DecodeCache {
+    /// `None` where the tape does not decode to an instruction.
+    decoded: Arc<Vec<Option<Instruction>>>,
+    /// Bit set of the addresses to decode again, empty until the first
+    /// invalidation.
+    stale: Vec<u64>,
+}
+
+impl DecodeCache {
+    pub fn new(memory: &MemoryBank) -> Self {
+        let decoded = (0..memory.tape_len())
+            .map(|address| Instruction::decode(address, memory.get_scalar_at(address), memory).ok())
+            .collect();
+        Self {
+            decoded: Arc::new(decoded),
+            stale: vec![],
+        }
+    }
+
+    /// Returns the instruction at `pc`, unless it has to be decoded again.
+    pub fn get(&self, pc: usize) -> Option<Instruction> {
+        if self
+            .stale
+            .get(pc / 64)
+            .is_some_and(|bits| bits & (1 << (pc % 64)) != 0)
+        {
+            return None;
+        }
+        self.decoded.get(pc).copied().flatten()
+    }
+
+    /// Marks the instructions `address` is part of as stale.
+    pub fn invalidate(&mut self, address: usize) {
+        for pc in address.saturating_sub(MAX_INSTRUCTION_WIDTH - 1)..=address {
+            let covered = matches!(
+                self.decoded.get(pc),
+                Some(Some(instruction)) if pc + instruction.width() > address
+            );
+            if covered {
+                if self.stale.is_empty() {
+                    self.stale = vec![0; self.decoded.len().div_ceil(64)];
+                }
+                self.stale[pc / 64] |= 1 << (pc % 64);
+            }
+        }
+    }
+
+    /// Whether the instruction cached at `pc`, if any, is made of the word at
+    /// `address`.
+    pub fn covers(&self, pc: usize, address: usize) -> bool {
+        match self.get(pc) {
+            Some(instruction) => (pc..pc + instruction.width()).contains(&address),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DecodeCache;
+    use crate::intcode::{Instruction, MemoryBank, Parameter::*};
+
+    #[test]
+    fn test_decode_cache() {
+        let memory = MemoryBank::new(vec![1002, 4, 3, 4, 99, 42]);
+        let mut cache = DecodeCache::new(&memory);
+        assert_eq!(
+            cache.get(0),
+            Some(Instruction::Multiply(
+                Position(4),
+                Immediate(3),
+                Position(4)
+            ))
+        );
+        assert_eq!(cache.get(4), Some(Instruction::Halt));
+        assert_eq!(cache.get(5), None);
+        assert_eq!(cache.get(100), None);
+        assert!(cache.covers(0, 3));
+        assert!(!cache.covers(0, 4));
+
+        let fork = cache.clone();
+        cache.invalidate(3);
+        assert_eq!(cache.get(0), None);
+        assert_eq!(cache.get(4), Some(Instruction::Halt));
+        assert!(fork.get(0).is_some());
+
+        cache.invalidate(4);
+        assert_eq!(cache.get(4), None);
+        cache.invalidate(1000);
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+
+use super::{
+    instruction::Instruction,
+    memory::{MemoryBank, Scalar},
+};
+
+/// What a disassembled region of the tape was decoded as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Disassembled {
+    Instruction(Instruction),
+    /// A word that does not decode to a complete, valid instruction.
+    Data(Scalar),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisassembledLine {
+    pub address: usize,
+    /// Raw tape words covered by this line.
+    pub words: Vec<Scalar>,
+    pub content: Disassembled,
+}
+
+/// Renders as `address: raw words    mnemonic`, e.g.
+/// `    0: 1002 4 3 4            MUL 4, #3, 4`.
+impl fmt::Display for DisassembledLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let words: Vec<String> = self.words.iter().map(|word| word.to_string()).collect();
+        write!(f, "{:>5}: {:<24} ", self.address, words.join(" "))?;
+        match self.content {
+            Disassembled::Instruction(instruction) => write!(f, "{}", instruction),
+            Disassembled::Data(word) => write!(f, "DATA {}", word),
+        }
+    }
+}
+
+/// Disassembles the loaded tape by linear sweep from address 0.
+///
+/// Words that do not decode to an instruction fitting in the tape are
+/// emitted one by one as `DATA`.
+pub fn disassemble(memory: &MemoryBank) -> Vec<DisassembledLine> {
+    let tape = memory.raw();
+    let mut lines = vec![];
+    let mut address = 0;
+    while address < tape.len() {
+        let line = match Instruction::decode(address, tape[address], memory) {
+            Ok(instruction) if address + instruction.width() <= tape.len() => DisassembledLine {
+                address,
+                words: tape[address..address + instruction.width()].to_vec(),
+                content: Disassembled::Instruction(instruction),
+            },
+            _ => DisassembledLine {
+                address,
+                words: vec![tape[address]],
+                content: Disassembled::Data(tape[address]),
+            },
+        };
+        address += line.words.len();
+        lines.push(line);
+    }
+    lines
+}
+
+/// Disassembles the loaded tape into a listing, one line per instruction.
+pub fn render_disassembly(memory: &MemoryBank) -> String {
+    disassemble(memory)
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{disassemble, render_disassembly, Disassembled};
+    use crate::intcode::{Instruction, MemoryBank, Parameter::*};
+
+    #[test]
+    fn test_disassemble() {
+        let memory = MemoryBank::new(vec![1002, 4, 3, 4, 33, 3, 0, 104, -1, 99, 1, 2]);
+        let lines = disassemble(&memory);
+        let contents: Vec<(usize, Disassembled)> = lines
+            .iter()
+            .map(|line| (line.address, line.content))
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                (
+                    0,
+                    Disassembled::Instruction(Instruction::Multiply(
+                        Position(4),
+                        Immediate(3),
+                        Position(4)
+                    ))
+                ),
+                (4, Disassembled::Data(33)),
+                (
+                    5,
+                    Disassembled::Instruction(Instruction::Input(Position(0)))
+                ),
+                (
+                    7,
+                    Disassembled::Instruction(Instruction::Output(Immediate(-1)))
+                ),
+                (9, Disassembled::Instruction(Instruction::Halt)),
+                // an ADD missing its last parameter
+                (10, Disassembled::Data(1)),
+                (11, Disassembled::Data(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_disassembly() {
+        let memory = MemoryBank::new(vec![109, 19, 204, -34, 99, 7]);
+        assert_eq!(
+            render_disassembly(&memory),
+            concat!(
+                "    0: 109 19                   ARB #19\n",
+                "    2: 204 -34                  OUT @-34\n",
+                "    4: 99                       HLT\n",
+                "    5: 7                        DATA 7\n",
+            )
+        );
+    }
+}
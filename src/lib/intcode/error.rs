@@ -0,0 +1,131 @@
+use std::{error::Error, fmt};
+
+use super::memory::Scalar;
+
+/// Failures of the Intcode virtual machine.
+///
+/// Every variant records the program counter of the faulting instruction
+/// and the raw instruction word found there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntcodeError {
+    /// The instruction word holds no known opcode.
+    UnknownOpcode { pc: usize, word: Scalar },
+    /// The mode digit of a parameter (1-based) is not a known mode.
+    InvalidMode {
+        pc: usize,
+        word: Scalar,
+        parameter: usize,
+        mode: Scalar,
+    },
+    /// A parameter resolved to a negative memory address.
+    NegativeAddress {
+        pc: usize,
+        word: Scalar,
+        address: Scalar,
+    },
+    /// A parameter (1-based) written to by the instruction is in immediate mode.
+    WriteInImmediateMode {
+        pc: usize,
+        word: Scalar,
+        parameter: usize,
+    },
+    /// An addition, multiplication or relative address does not fit in a
+    /// `Scalar`.
+    ArithmeticOverflow { pc: usize, word: Scalar },
+    /// The program needs an input the input device cannot provide.
+    InputExhausted { pc: usize, word: Scalar },
+    /// The program did not halt within its budget of executed instructions.
+    StepLimitExceeded { pc: usize, word: Scalar, limit: u64 },
+    /// The whole machine state repeated itself without any I/O in between:
+    /// `pc` is where the loop starts, and the loop takes `period`
+    /// instructions.
+    InfiniteLoop {
+        pc: usize,
+        word: Scalar,
+        period: u64,
+    },
+}
+
+pub type IntcodeResult<T> = Result<T, IntcodeError>;
+
+impl IntcodeError {
+    /// Program counter of the faulting instruction.
+    pub fn pc(&self) -> usize {
+        match *self {
+            IntcodeError::UnknownOpcode { pc, .. }
+            | IntcodeError::InvalidMode { pc, .. }
+            | IntcodeError::NegativeAddress { pc, .. }
+            | IntcodeError::WriteInImmediateMode { pc, .. }
+            | IntcodeError::ArithmeticOverflow { pc, .. }
+            | IntcodeError::InputExhausted { pc, .. }
+            | IntcodeError::StepLimitExceeded { pc, .. }
+            | IntcodeError::InfiniteLoop { pc, .. } => pc,
+        }
+    }
+
+    /// Raw instruction word of the faulting instruction.
+    pub fn word(&self) -> Scalar {
+        match *self {
+            IntcodeError::UnknownOpcode { word, .. }
+            | IntcodeError::InvalidMode { word, .. }
+            | IntcodeError::NegativeAddress { word, .. }
+            | IntcodeError::WriteInImmediateMode { word, .. }
+            | IntcodeError::ArithmeticOverflow { word, .. }
+            | IntcodeError::InputExhausted { word, .. }
+            | IntcodeError::StepLimitExceeded { word, .. }
+            | IntcodeError::InfiniteLoop { word, .. } => word,
+        }
+    }
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            IntcodeError::UnknownOpcode { pc, word } => {
+                write!(f, "unknown opcode in word {} at {}", word, pc)
+            }
+            IntcodeError::InvalidMode {
+                pc,
+                word,
+                parameter,
+                mode,
+            } => write!(
+                f,
+                "invalid mode {} for parameter {} in word {} at {}",
+                mode, parameter, word, pc
+            ),
+            IntcodeError::NegativeAddress { pc, word, address } => write!(
+                f,
+                "negative memory address {} used by word {} at {}",
+                address, word, pc
+            ),
+            IntcodeError::WriteInImmediateMode {
+                pc,
+                word,
+                parameter,
+            } => write!(
+                f,
+                "write parameter {} in immediate mode in word {} at {}",
+                parameter, word, pc
+            ),
+            IntcodeError::ArithmeticOverflow { pc, word } => {
+                write!(f, "arithmetic overflow in word {} at {}", word, pc)
+            }
+            IntcodeError::InputExhausted { pc, word } => {
+                write!(f, "no input available for word {} at {}", word, pc)
+            }
+            IntcodeError::StepLimitExceeded { pc, word, limit } => write!(
+                f,
+                "step limit of {} exceeded at word {} at {}",
+                limit, word, pc
+            ),
+            IntcodeError::InfiniteLoop { pc, word, period } => write!(
+                f,
+                "infinite loop of {} instructions through word {} at {}",
+                period, word, pc
+            ),
+        }
+    }
+}
+
+impl Error for IntcodeError {}
@@ -0,0 +1,316 @@
+//! Static control-flow graph extraction.
+//!
+//! Only jumps with an immediate target are resolved: other targets are only
+//! known at run time, and reported as such.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{
+    instruction::{Instruction, Parameter},
+    memory::{scalar_to_address, MemoryBank, Scalar},
+};
+
+/// How control leaves a basic block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockExit {
+    /// Execution continues at the given address, which starts another block.
+    FallThrough(usize),
+    /// `JT`/`JF` whose immediate condition always jumps, to the given address
+    /// if it is known statically.
+    Jump(Option<usize>),
+    /// `JT`/`JF` that either jumps, to `target` if it is known statically, or
+    /// continues at `fall_through`.
+    Branch {
+        target: Option<usize>,
+        fall_through: usize,
+    },
+    Halt,
+}
+
+impl BlockExit {
+    /// How `instruction`, decoded at `pc`, ends a block, if it does.
+    ///
+    /// Jumps whose immediate condition never jumps do not.
+    pub(super) fn of(pc: usize, instruction: Instruction) -> Option<Self> {
+        let (condition, target, jumps_if_true) = match instruction {
+            Instruction::Halt => return Some(BlockExit::Halt),
+            Instruction::JumpIfTrue(condition, target) => (condition, target, true),
+            Instruction::JumpIfFalse(condition, target) => (condition, target, false),
+            _ => return None,
+        };
+        let target = match target {
+            Parameter::Immediate(target) => scalar_to_address(target),
+            _ => None,
+        };
+        match condition {
+            Parameter::Immediate(value) if (value != 0) == jumps_if_true => {
+                Some(BlockExit::Jump(target))
+            }
+            Parameter::Immediate(_) => None,
+            _ => Some(BlockExit::Branch {
+                target,
+                fall_through: pc + instruction.width(),
+            }),
+        }
+    }
+
+    /// Statically known addresses control may go to.
+    pub fn successors(&self) -> Vec<usize> {
+        match *self {
+            BlockExit::FallThrough(next) => vec![next],
+            BlockExit::Jump(target) => target.into_iter().collect(),
+            BlockExit::Branch {
+                target,
+                fall_through,
+            } => target.into_iter().chain([fall_through]).collect(),
+            BlockExit::Halt => vec![],
+        }
+    }
+
+    /// Whether control may go to an address only known at run time.
+    pub fn is_dynamic(&self) -> bool {
+        matches!(
+            self,
+            BlockExit::Jump(None) | BlockExit::Branch { target: None, .. }
+        )
+    }
+}
+
+/// Straight-line run of instructions, only entered at its first one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BasicBlock {
+    pub start: usize,
+    /// Structure: (address, instruction), in execution order.
+    pub instructions: Vec<(usize, Instruction)>,
+    pub exit: BlockExit,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    /// Basic blocks by start address.
+    pub blocks: BTreeMap<usize, BasicBlock>,
+    /// Words control reaches that do not decode to an instruction, e.g. ones
+    /// the program rewrites before executing them, by address.
+    pub invalid: BTreeMap<usize, Scalar>,
+}
+
+impl ControlFlowGraph {
+    /// Follows the program loaded in `memory` from address 0.
+    pub fn from_memory(memory: &MemoryBank) -> Self {
+        Self::from_entries(memory, &[0])
+    }
+
+    /// Follows the program loaded in `memory` from each of `entries`, e.g.
+    /// the targets of jumps only known at run time.
+    pub fn from_entries(memory: &MemoryBank, entries: &[usize]) -> Self {
+        let mut graph = Self::default();
+        let mut instructions = BTreeMap::new();
+        // an instruction starts a block if it can be reached in several ways
+        let mut predecessors: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut leaders: BTreeSet<usize> = entries.iter().copied().collect();
+        let mut pending = entries.to_vec();
+        while let Some(pc) = pending.pop() {
+            if instructions.contains_key(&pc) || graph.invalid.contains_key(&pc) {
+                continue;
+            }
+            let word = memory.get_scalar_at(pc);
+            let instruction = match Instruction::decode(pc, word, memory) {
+                Ok(instruction) => instruction,
+                Err(_) => {
+                    graph.invalid.insert(pc, word);
+                    continue;
+                }
+            };
+            instructions.insert(pc, instruction);
+            let successors = match BlockExit::of(pc, instruction) {
+                Some(exit) => {
+                    leaders.extend(exit.successors());
+                    exit.successors()
+                }
+                None => vec![pc + instruction.width()],
+            };
+            for successor in successors {
+                *predecessors.entry(successor).or_insert(0) += 1;
+                pending.push(successor);
+            }
+        }
+        leaders.extend(
+            predecessors
+                .into_iter()
+                .filter(|&(_, count)| count > 1)
+                .map(|(address, _)| address),
+        );
+
+        for &start in &leaders {
+            let mut pc = start;
+            let mut block_instructions = vec![];
+            let exit = loop {
+                let instruction = match instructions.get(&pc) {
+                    Some(&instruction) => instruction,
+                    None => break None,
+                };
+                block_instructions.push((pc, instruction));
+                if let Some(exit) = BlockExit::of(pc, instruction) {
+                    break Some(exit);
+                }
+                pc += instruction.width();
+                if leaders.contains(&pc) || !instructions.contains_key(&pc) {
+                    break Some(BlockExit::FallThrough(pc));
+                }
+            };
+            if let Some(exit) = exit {
+                graph.blocks.insert(
+                    start,
+                    BasicBlock {
+                        start,
+                        instructions: block_instructions,
+                        exit,
+                    },
+                );
+            }
+        }
+        graph
+    }
+
+    /// Renders the graph in the Graphviz DOT language, one node per block
+    /// and per invalid word, plus a `dynamic` node standing for every target
+    /// only known at run time.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph intcode {\n");
+        dot += "    node [shape=box, fontname=\"monospace\"];\n";
+        for block in self.blocks.values() {
+            let label: String = block
+                .instructions
+                .iter()
+                .map(|(address, instruction)| format!("{}: {}\\l", address, instruction))
+                .collect();
+            dot += &format!("    n{} [label=\"{}\"];\n", block.start, label);
+        }
+        for (address, word) in &self.invalid {
+            dot += &format!(
+                "    n{} [label=\"{}: DATA {}\\l\", style=dashed];\n",
+                address, address, word
+            );
+        }
+        if self.blocks.values().any(|block| block.exit.is_dynamic()) {
+            dot += "    dynamic [label=\"?\", shape=circle, style=dashed];\n";
+        }
+
+        let node = |target: Option<usize>| match target {
+            Some(address) => format!("n{}", address),
+            None => "dynamic".to_string(),
+        };
+        for block in self.blocks.values() {
+            let from = block.start;
+            match block.exit {
+                BlockExit::FallThrough(next) => dot += &format!("    n{} -> n{};\n", from, next),
+                BlockExit::Jump(target) => {
+                    dot += &format!("    n{} -> {};\n", from, node(target));
+                }
+                BlockExit::Branch {
+                    target,
+                    fall_through,
+                } => {
+                    dot += &format!("    n{} -> {} [label=\"jump\"];\n", from, node(target));
+                    dot += &format!("    n{} -> n{} [label=\"next\"];\n", from, fall_through);
+                }
+                BlockExit::Halt => {}
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockExit, ControlFlowGraph};
+    use crate::intcode::{assemble, MemoryBank};
+
+    #[test]
+    fn test_control_flow_graph() {
+        let tape = assemble(
+            "
+            loop: in 100
+                  jf 100, #end
+                  out 100
+                  jt #1, #loop
+                  data 42
+            end:  jt 101, 101
+                  hlt
+        ",
+        )
+        .unwrap();
+        let graph = ControlFlowGraph::from_memory(&MemoryBank::new(tape));
+        let exits: Vec<(usize, usize, BlockExit)> = graph
+            .blocks
+            .values()
+            .map(|block| (block.start, block.instructions.len(), block.exit))
+            .collect();
+        assert_eq!(
+            exits,
+            vec![
+                (
+                    0,
+                    2,
+                    BlockExit::Branch {
+                        target: Some(11),
+                        fall_through: 5
+                    }
+                ),
+                (5, 2, BlockExit::Jump(Some(0))),
+                (
+                    11,
+                    1,
+                    BlockExit::Branch {
+                        target: None,
+                        fall_through: 14
+                    }
+                ),
+                (14, 1, BlockExit::Halt),
+            ]
+        );
+        assert!(graph.invalid.is_empty());
+
+        // a jump into the middle of a block splits it
+        let tape = assemble("add #1, #2, 100\nout 100\njt #1, #4\n").unwrap();
+        let graph = ControlFlowGraph::from_memory(&MemoryBank::new(tape));
+        assert_eq!(graph.blocks.keys().copied().collect::<Vec<_>>(), vec![0, 4]);
+        assert_eq!(graph.blocks[&0].exit, BlockExit::FallThrough(4));
+        assert_eq!(graph.blocks[&4].exit, BlockExit::Jump(Some(4)));
+    }
+
+    #[test]
+    fn test_control_flow_graph_entries_and_invalid_words() {
+        // the first instruction rewrites the second one into `OUT #7`
+        let memory = MemoryBank::new(vec![1101, 100, 4, 4, 0, 7, 99]);
+        let graph = ControlFlowGraph::from_memory(&memory);
+        assert_eq!(graph.blocks[&0].exit, BlockExit::FallThrough(4));
+        assert_eq!(graph.invalid.get(&4), Some(&0));
+
+        let graph = ControlFlowGraph::from_entries(&memory, &[0, 6]);
+        assert_eq!(graph.blocks.keys().copied().collect::<Vec<_>>(), vec![0, 6]);
+        assert_eq!(graph.blocks[&6].exit, BlockExit::Halt);
+    }
+
+    #[test]
+    fn test_control_flow_graph_to_dot() {
+        let tape = assemble("in 100\njt 100, 100\nout 100\nhlt\ndata 0").unwrap();
+        let mut memory = MemoryBank::new(tape);
+        memory.set_scalar_at(8, 55);
+        let graph = ControlFlowGraph::from_entries(&memory, &[0, 8]);
+        assert_eq!(
+            graph.to_dot(),
+            "digraph intcode {
+    node [shape=box, fontname=\"monospace\"];
+    n0 [label=\"0: IN 100\\l2: JT 100, 100\\l\"];
+    n5 [label=\"5: OUT 100\\l7: HLT\\l\"];
+    n8 [label=\"8: DATA 55\\l\", style=dashed];
+    dynamic [label=\"?\", shape=circle, style=dashed];
+    n0 -> dynamic [label=\"jump\"];
+    n0 -> n5 [label=\"next\"];
+}
+"
+        );
+    }
+}
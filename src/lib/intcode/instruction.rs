@@ -0,0 +1,655 @@
+use std::fmt;
+
+use super::{
+    error::{IntcodeError, IntcodeResult},
+    io::InputDevice,
+    memory::{scalar_to_address, MemoryBank, Scalar},
+};
+
+const OPERATION_CODE_ADD: Scalar = 1;
+const OPERATION_CODE_MULTIPLY: Scalar = 2;
+const OPERATION_CODE_INPUT: Scalar = 3;
+const OPERATION_CODE_OUTPUT: Scalar = 4;
+const OPERATION_CODE_JUMP_IF_TRUE: Scalar = 5;
+const OPERATION_CODE_JUMP_IF_FALSE: Scalar = 6;
+const OPERATION_CODE_LESS_THAN: Scalar = 7;
+const OPERATION_CODE_EQUALS: Scalar = 8;
+const OPERATION_CODE_ADJUST_RELATIVE_BASE: Scalar = 9;
+const OPERATION_CODE_HALT: Scalar = 99;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstructionMode {
+    /// Mode 0: instruction parameter interpreted as position.
+    Position = 0,
+    /// Mode 1: instruction parameter interpreted as value.
+    Immediate = 1,
+    /// Mode 2: instruction parameter interpreted as position, offset by the relative base.
+    Relative = 2,
+}
+
+impl InstructionMode {
+    pub fn from_digit(digit: Scalar) -> Option<Self> {
+        match digit {
+            0 => Some(InstructionMode::Position),
+            1 => Some(InstructionMode::Immediate),
+            2 => Some(InstructionMode::Relative),
+            _ => None,
+        }
+    }
+}
+
+/// An instruction parameter, as read from the tape and tagged with its mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameter {
+    /// The value lives in memory at the given address.
+    Position(usize),
+    /// The parameter is the value itself.
+    Immediate(Scalar),
+    /// The value lives in memory at the given offset from the relative base.
+    Relative(Scalar),
+}
+
+impl Parameter {
+    /// Returns `None` if `raw` is negative in position mode.
+    pub fn new(mode: InstructionMode, raw: Scalar) -> Option<Self> {
+        Some(match mode {
+            InstructionMode::Position => Parameter::Position(scalar_to_address(raw)?),
+            InstructionMode::Immediate => Parameter::Immediate(raw),
+            InstructionMode::Relative => Parameter::Relative(raw),
+        })
+    }
+
+    pub fn mode(&self) -> InstructionMode {
+        match *self {
+            Parameter::Position(_) => InstructionMode::Position,
+            Parameter::Immediate(_) => InstructionMode::Immediate,
+            Parameter::Relative(_) => InstructionMode::Relative,
+        }
+    }
+
+    /// Memory address this parameter designates, which may be negative for a
+    /// relative parameter. Returns `None` for an immediate parameter, or if
+    /// the relative address overflows.
+    pub fn address(&self, relative_base: Scalar) -> Option<Scalar> {
+        match *self {
+            Parameter::Position(at) => Some(at as Scalar),
+            Parameter::Immediate(_) => None,
+            Parameter::Relative(offset) => relative_base.checked_add(offset),
+        }
+    }
+}
+
+/// Position parameters are printed as bare addresses, immediate ones with a
+/// `#` prefix and relative ones with a `@` prefix.
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Parameter::Position(at) => write!(f, "{}", at),
+            Parameter::Immediate(value) => write!(f, "#{}", value),
+            Parameter::Relative(offset) => write!(f, "@{}", offset),
+        }
+    }
+}
+
+/// What the virtual machine must do once an instruction has been applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    /// Move on to the instruction right after this one.
+    Continue,
+    /// Move the program counter to the given address.
+    Jump(usize),
+    /// Add the given offset to the relative base, then continue. The sum is
+    /// known not to overflow.
+    AdjustRelativeBase(Scalar),
+    /// Emit the given value, then continue.
+    Output(Scalar),
+    /// Stay on this instruction: no input is available yet.
+    NeedsInput,
+    /// Stop the program.
+    Halt,
+}
+
+/// Resolves the parameters of the instruction found at `pc`.
+struct Operands<'a> {
+    pc: usize,
+    word: Scalar,
+    relative_base: Scalar,
+    memory: &'a mut MemoryBank,
+}
+
+impl Operands<'_> {
+    fn to_address(&self, address: Scalar) -> IntcodeResult<usize> {
+        scalar_to_address(address).ok_or(IntcodeError::NegativeAddress {
+            pc: self.pc,
+            word: self.word,
+            address,
+        })
+    }
+
+    /// Fails on overflow, e.g. to check the arithmetic of the instruction.
+    fn checked(&self, value: Option<Scalar>) -> IntcodeResult<Scalar> {
+        value.ok_or(IntcodeError::ArithmeticOverflow {
+            pc: self.pc,
+            word: self.word,
+        })
+    }
+
+    fn relative_address(&self, offset: Scalar) -> IntcodeResult<usize> {
+        self.to_address(self.checked(self.relative_base.checked_add(offset))?)
+    }
+
+    fn read(&self, parameter: Parameter) -> IntcodeResult<Scalar> {
+        Ok(match parameter {
+            Parameter::Position(at) => self.memory.get_scalar_at(at),
+            Parameter::Immediate(value) => value,
+            Parameter::Relative(offset) => {
+                self.memory.get_scalar_at(self.relative_address(offset)?)
+            }
+        })
+    }
+
+    /// `index` is the 1-based position of the written parameter.
+    fn write(&mut self, parameter: Parameter, index: usize, value: Scalar) -> IntcodeResult<()> {
+        let address = match parameter {
+            Parameter::Position(at) => at,
+            Parameter::Immediate(_) => {
+                return Err(IntcodeError::WriteInImmediateMode {
+                    pc: self.pc,
+                    word: self.word,
+                    parameter: index,
+                })
+            }
+            Parameter::Relative(offset) => self.relative_address(offset)?,
+        };
+        self.memory.set_scalar_at(address, value);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    /// Structure: (lhs, rhs, output_at)
+    Add(Parameter, Parameter, Parameter),
+    /// Structure: (lhs, rhs, output_at)
+    Multiply(Parameter, Parameter, Parameter),
+    /// Takes a single integer as input and saves it to the position given by its only parameter.
+    Input(Parameter),
+    /// Outputs the value of its only parameter.
+    Output(Parameter),
+    /// Jumps to `target` if `condition` is non-zero.
+    ///
+    /// Structure: (condition, target)
+    JumpIfTrue(Parameter, Parameter),
+    /// Jumps to `target` if `condition` is zero.
+    ///
+    /// Structure: (condition, target)
+    JumpIfFalse(Parameter, Parameter),
+    /// Stores 1 if `lhs < rhs`, 0 otherwise.
+    ///
+    /// Structure: (lhs, rhs, output_at)
+    LessThan(Parameter, Parameter, Parameter),
+    /// Stores 1 if `lhs == rhs`, 0 otherwise.
+    ///
+    /// Structure: (lhs, rhs, output_at)
+    Equals(Parameter, Parameter, Parameter),
+    /// Adds the value of its only parameter to the relative base.
+    AdjustRelativeBase(Parameter),
+    /// Immediately halts the program.
+    Halt,
+}
+
+impl Instruction {
+    pub fn code(&self) -> Scalar {
+        match *self {
+            Instruction::Add(_, _, _) => OPERATION_CODE_ADD,
+            Instruction::Multiply(_, _, _) => OPERATION_CODE_MULTIPLY,
+            Instruction::Input(_) => OPERATION_CODE_INPUT,
+            Instruction::Output(_) => OPERATION_CODE_OUTPUT,
+            Instruction::JumpIfTrue(_, _) => OPERATION_CODE_JUMP_IF_TRUE,
+            Instruction::JumpIfFalse(_, _) => OPERATION_CODE_JUMP_IF_FALSE,
+            Instruction::LessThan(_, _, _) => OPERATION_CODE_LESS_THAN,
+            Instruction::Equals(_, _, _) => OPERATION_CODE_EQUALS,
+            Instruction::AdjustRelativeBase(_) => OPERATION_CODE_ADJUST_RELATIVE_BASE,
+            Instruction::Halt => OPERATION_CODE_HALT,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match *self {
+            Instruction::Add(_, _, _) => "ADD",
+            Instruction::Multiply(_, _, _) => "MUL",
+            Instruction::Input(_) => "IN",
+            Instruction::Output(_) => "OUT",
+            Instruction::JumpIfTrue(_, _) => "JT",
+            Instruction::JumpIfFalse(_, _) => "JF",
+            Instruction::LessThan(_, _, _) => "LT",
+            Instruction::Equals(_, _, _) => "EQ",
+            Instruction::AdjustRelativeBase(_) => "ARB",
+            Instruction::Halt => "HLT",
+        }
+    }
+
+    /// Parameters of the instruction, in tape order.
+    pub fn parameters(&self) -> Vec<Parameter> {
+        match *self {
+            Instruction::Add(a, b, c)
+            | Instruction::Multiply(a, b, c)
+            | Instruction::LessThan(a, b, c)
+            | Instruction::Equals(a, b, c) => vec![a, b, c],
+            Instruction::JumpIfTrue(a, b) | Instruction::JumpIfFalse(a, b) => vec![a, b],
+            Instruction::Input(a) | Instruction::Output(a) | Instruction::AdjustRelativeBase(a) => {
+                vec![a]
+            }
+            Instruction::Halt => vec![],
+        }
+    }
+
+    /// Parameter designating the memory written by the instruction, if any.
+    pub fn target(&self) -> Option<Parameter> {
+        match *self {
+            Instruction::Add(_, _, c)
+            | Instruction::Multiply(_, _, c)
+            | Instruction::LessThan(_, _, c)
+            | Instruction::Equals(_, _, c) => Some(c),
+            Instruction::Input(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Encodes the instruction back into its tape words, the inverse of `decode`.
+    pub fn encode(&self) -> Vec<Scalar> {
+        let parameters = self.parameters();
+        let mut code = self.code();
+        let mut words = Vec::with_capacity(1 + parameters.len());
+        words.push(0);
+        for (i, parameter) in parameters.iter().enumerate() {
+            code += parameter.mode() as Scalar * 10i64.pow(i as u32 + 2);
+            words.push(match *parameter {
+                Parameter::Position(at) => at as Scalar,
+                Parameter::Immediate(value) | Parameter::Relative(value) => value,
+            });
+        }
+        words[0] = code;
+        words
+    }
+
+    /// Number of words taken by the instruction, opcode included.
+    pub fn width(&self) -> usize {
+        match *self {
+            Instruction::Add(_, _, _)
+            | Instruction::Multiply(_, _, _)
+            | Instruction::LessThan(_, _, _)
+            | Instruction::Equals(_, _, _) => 4,
+            Instruction::JumpIfTrue(_, _) | Instruction::JumpIfFalse(_, _) => 3,
+            Instruction::Input(_) | Instruction::Output(_) | Instruction::AdjustRelativeBase(_) => {
+                2
+            }
+            Instruction::Halt => 1,
+        }
+    }
+
+    /// Decodes the instruction word `code` found at `pc`.
+    ///
+    /// The two rightmost digits of `code` hold the opcode, and each digit
+    /// above them holds the mode of one parameter, read right-to-left
+    /// (a missing digit means `Position`).
+    pub fn decode(pc: usize, code: Scalar, memory: &MemoryBank) -> IntcodeResult<Self> {
+        let opcode = code % 100;
+        let modes = code / 100;
+
+        let parameter = |index: u32| -> IntcodeResult<Parameter> {
+            let digit = modes / 10i64.pow(index) % 10;
+            let mode = InstructionMode::from_digit(digit).ok_or(IntcodeError::InvalidMode {
+                pc,
+                word: code,
+                parameter: index as usize + 1,
+                mode: digit,
+            })?;
+            let raw = memory.get_scalar_at(pc + 1 + index as usize);
+            Parameter::new(mode, raw).ok_or(IntcodeError::NegativeAddress {
+                pc,
+                word: code,
+                address: raw,
+            })
+        };
+        let target = |index: u32| -> IntcodeResult<Parameter> {
+            match parameter(index)? {
+                Parameter::Immediate(_) => Err(IntcodeError::WriteInImmediateMode {
+                    pc,
+                    word: code,
+                    parameter: index as usize + 1,
+                }),
+                target => Ok(target),
+            }
+        };
+
+        Ok(match opcode {
+            OPERATION_CODE_ADD => Instruction::Add(parameter(0)?, parameter(1)?, target(2)?),
+            OPERATION_CODE_MULTIPLY => {
+                Instruction::Multiply(parameter(0)?, parameter(1)?, target(2)?)
+            }
+            OPERATION_CODE_INPUT => Instruction::Input(target(0)?),
+            OPERATION_CODE_OUTPUT => Instruction::Output(parameter(0)?),
+            OPERATION_CODE_JUMP_IF_TRUE => Instruction::JumpIfTrue(parameter(0)?, parameter(1)?),
+            OPERATION_CODE_JUMP_IF_FALSE => Instruction::JumpIfFalse(parameter(0)?, parameter(1)?),
+            OPERATION_CODE_LESS_THAN => {
+                Instruction::LessThan(parameter(0)?, parameter(1)?, target(2)?)
+            }
+            OPERATION_CODE_EQUALS => Instruction::Equals(parameter(0)?, parameter(1)?, target(2)?),
+            OPERATION_CODE_ADJUST_RELATIVE_BASE => Instruction::AdjustRelativeBase(parameter(0)?),
+            OPERATION_CODE_HALT => Instruction::Halt,
+            _ => return Err(IntcodeError::UnknownOpcode { pc, word: code }),
+        })
+    }
+
+    /// Applies the instruction decoded at `pc`.
+    pub fn apply(
+        &self,
+        pc: usize,
+        memory: &mut MemoryBank,
+        relative_base: Scalar,
+        input: &mut dyn InputDevice,
+    ) -> IntcodeResult<Effect> {
+        let mut operands = Operands {
+            pc,
+            word: memory.get_scalar_at(pc),
+            relative_base,
+            memory,
+        };
+        Ok(match *self {
+            Instruction::Add(lhs, rhs, output_at) => {
+                let (lhs, rhs) = (operands.read(lhs)?, operands.read(rhs)?);
+                let sum = operands.checked(lhs.checked_add(rhs))?;
+                operands.write(output_at, 3, sum)?;
+                Effect::Continue
+            }
+            Instruction::Multiply(lhs, rhs, output_at) => {
+                let (lhs, rhs) = (operands.read(lhs)?, operands.read(rhs)?);
+                let product = operands.checked(lhs.checked_mul(rhs))?;
+                operands.write(output_at, 3, product)?;
+                Effect::Continue
+            }
+            Instruction::Input(output_at) => match input.read() {
+                Some(value) => {
+                    operands.write(output_at, 1, value)?;
+                    Effect::Continue
+                }
+                None => Effect::NeedsInput,
+            },
+            Instruction::Output(value) => Effect::Output(operands.read(value)?),
+            Instruction::JumpIfTrue(condition, target) => {
+                if operands.read(condition)? != 0 {
+                    Effect::Jump(operands.to_address(operands.read(target)?)?)
+                } else {
+                    Effect::Continue
+                }
+            }
+            Instruction::JumpIfFalse(condition, target) => {
+                if operands.read(condition)? == 0 {
+                    Effect::Jump(operands.to_address(operands.read(target)?)?)
+                } else {
+                    Effect::Continue
+                }
+            }
+            Instruction::LessThan(lhs, rhs, output_at) => {
+                let (lhs, rhs) = (operands.read(lhs)?, operands.read(rhs)?);
+                operands.write(output_at, 3, Scalar::from(lhs < rhs))?;
+                Effect::Continue
+            }
+            Instruction::Equals(lhs, rhs, output_at) => {
+                let (lhs, rhs) = (operands.read(lhs)?, operands.read(rhs)?);
+                operands.write(output_at, 3, Scalar::from(lhs == rhs))?;
+                Effect::Continue
+            }
+            Instruction::AdjustRelativeBase(offset) => {
+                let offset = operands.read(offset)?;
+                // the virtual machine can then adjust the base unchecked
+                operands.checked(relative_base.checked_add(offset))?;
+                Effect::AdjustRelativeBase(offset)
+            }
+            Instruction::Halt => Effect::Halt,
+        })
+    }
+}
+
+/// Renders the instruction as its mnemonic followed by its parameters,
+/// e.g. `MUL 4, #3, @-1`.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        for (i, parameter) in self.parameters().iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { ", " }, parameter)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Effect, Instruction, Parameter::*};
+    use crate::intcode::{IntcodeError, MemoryBank, QueueDevice, Scalar};
+
+    #[test]
+    fn test_instruction_decode_modes() {
+        let memory = MemoryBank::new(vec![1002, 4, 3, 4, 33]);
+        assert_eq!(
+            Instruction::decode(0, 1002, &memory).unwrap(),
+            Instruction::Multiply(Position(4), Immediate(3), Position(4))
+        );
+
+        let memory = MemoryBank::new(vec![1101, 100, 7, 4, 0]);
+        assert_eq!(
+            Instruction::decode(0, 1101, &memory).unwrap(),
+            Instruction::Add(Immediate(100), Immediate(7), Position(4))
+        );
+
+        let memory = MemoryBank::new(vec![22201, 1, -2, 3, 109, 19]);
+        assert_eq!(
+            Instruction::decode(0, 22201, &memory).unwrap(),
+            Instruction::Add(Relative(1), Relative(-2), Relative(3))
+        );
+        assert_eq!(
+            Instruction::decode(4, 109, &memory).unwrap(),
+            Instruction::AdjustRelativeBase(Immediate(19))
+        );
+
+        let memory = MemoryBank::new(vec![1, 0, 0, 0, 99]);
+        assert_eq!(
+            Instruction::decode(0, 1, &memory).unwrap(),
+            Instruction::Add(Position(0), Position(0), Position(0))
+        );
+        assert_eq!(
+            Instruction::decode(4, 99, &memory).unwrap(),
+            Instruction::Halt
+        );
+    }
+
+    #[test]
+    fn test_instruction_width() {
+        let memory = MemoryBank::new(vec![1002, 4, 3, 4, 33, 3, 0, 4, 0, 99]);
+        assert_eq!(Instruction::decode(0, 1002, &memory).unwrap().width(), 4);
+        assert_eq!(Instruction::decode(5, 3, &memory).unwrap().width(), 2);
+        assert_eq!(Instruction::decode(7, 4, &memory).unwrap().width(), 2);
+        assert_eq!(Instruction::decode(9, 99, &memory).unwrap().width(), 1);
+    }
+
+    #[test]
+    fn test_instruction_apply_jumps_and_comparisons() {
+        let mut memory =
+            MemoryBank::new(vec![1105, 1, 9, 1106, 1, 9, 1107, 1, 2, 0, 1108, 3, 3, 0]);
+        let mut input = QueueDevice::new();
+        let mut apply_at = |pc: usize, memory: &mut MemoryBank| {
+            let code = memory.get_scalar_at(pc);
+            let instruction = Instruction::decode(pc, code, memory).unwrap();
+            instruction.apply(pc, memory, 0, &mut input).unwrap()
+        };
+
+        assert_eq!(apply_at(0, &mut memory), Effect::Jump(9));
+        assert_eq!(apply_at(3, &mut memory), Effect::Continue);
+        assert_eq!(apply_at(6, &mut memory), Effect::Continue);
+        assert_eq!(memory.get_scalar_at(0), 1);
+        assert_eq!(apply_at(10, &mut memory), Effect::Continue);
+        assert_eq!(memory.get_scalar_at(0), 1);
+    }
+
+    #[test]
+    fn test_instruction_decode_errors() {
+        let memory = MemoryBank::new(vec![11101, 1, 1, 4, 99]);
+        assert_eq!(
+            Instruction::decode(0, 11101, &memory),
+            Err(IntcodeError::WriteInImmediateMode {
+                pc: 0,
+                word: 11101,
+                parameter: 3
+            })
+        );
+
+        let memory = MemoryBank::new(vec![99, 301, 1, 1, 4, 99]);
+        assert_eq!(
+            Instruction::decode(1, 301, &memory),
+            Err(IntcodeError::InvalidMode {
+                pc: 1,
+                word: 301,
+                parameter: 1,
+                mode: 3
+            })
+        );
+
+        let memory = MemoryBank::new(vec![42, 0, 0, 0]);
+        assert_eq!(
+            Instruction::decode(0, 42, &memory),
+            Err(IntcodeError::UnknownOpcode { pc: 0, word: 42 })
+        );
+
+        let memory = MemoryBank::new(vec![1, -1, 0, 0, 99]);
+        assert_eq!(
+            Instruction::decode(0, 1, &memory),
+            Err(IntcodeError::NegativeAddress {
+                pc: 0,
+                word: 1,
+                address: -1
+            })
+        );
+        let memory = MemoryBank::new(vec![101, -1, 0, 0, 99]);
+        assert!(Instruction::decode(0, 101, &memory).is_ok());
+    }
+
+    #[test]
+    fn test_instruction_apply_modes() {
+        let mut memory = MemoryBank::new(vec![1002, 4, 3, 4, 33]);
+        let instruction = Instruction::decode(0, 1002, &memory).unwrap();
+        let mut input = QueueDevice::new();
+        assert_eq!(
+            instruction.apply(0, &mut memory, 0, &mut input).unwrap(),
+            Effect::Continue
+        );
+        assert_eq!(memory.raw(), &[1002, 4, 3, 4, 99]);
+    }
+
+    #[test]
+    fn test_instruction_apply_input_output() {
+        let mut memory = MemoryBank::new(vec![3, 0, 104, 7, 4, 0]);
+        let mut input = QueueDevice::from(vec![42]);
+
+        let instruction = Instruction::decode(0, 3, &memory).unwrap();
+        assert_eq!(instruction, Instruction::Input(Position(0)));
+        instruction.apply(0, &mut memory, 0, &mut input).unwrap();
+        assert_eq!(memory.get_scalar_at(0), 42);
+        assert_eq!(
+            instruction.apply(0, &mut memory, 0, &mut input).unwrap(),
+            Effect::NeedsInput
+        );
+
+        let instruction = Instruction::decode(2, 104, &memory).unwrap();
+        assert_eq!(instruction, Instruction::Output(Immediate(7)));
+        assert_eq!(
+            instruction.apply(2, &mut memory, 0, &mut input).unwrap(),
+            Effect::Output(7)
+        );
+        let instruction = Instruction::decode(4, 4, &memory).unwrap();
+        assert_eq!(
+            instruction.apply(4, &mut memory, 0, &mut input).unwrap(),
+            Effect::Output(42)
+        );
+    }
+
+    #[test]
+    fn test_instruction_apply_relative_mode() {
+        let mut memory = MemoryBank::new(vec![109, -3, 204, 1, 21101, 2, 3, -1, 5]);
+        let mut input = QueueDevice::new();
+
+        let instruction = Instruction::decode(0, 109, &memory).unwrap();
+        assert_eq!(
+            instruction.apply(0, &mut memory, 0, &mut input).unwrap(),
+            Effect::AdjustRelativeBase(-3)
+        );
+
+        let instruction = Instruction::decode(2, 204, &memory).unwrap();
+        assert_eq!(
+            instruction.apply(2, &mut memory, 7, &mut input).unwrap(),
+            Effect::Output(5)
+        );
+
+        let instruction = Instruction::decode(4, 21101, &memory).unwrap();
+        instruction.apply(4, &mut memory, 10, &mut input).unwrap();
+        assert_eq!(memory.get_scalar_at(9), 5);
+        assert_eq!(
+            instruction.apply(4, &mut memory, 0, &mut input),
+            Err(IntcodeError::NegativeAddress {
+                pc: 4,
+                word: 21101,
+                address: -1
+            })
+        );
+    }
+
+    #[test]
+    fn test_instruction_apply_overflow() {
+        let mut input = QueueDevice::new();
+        for (tape, relative_base) in [
+            (vec![1101, Scalar::MAX, 1, 0], 0),
+            (vec![1102, Scalar::MAX, 2, 0], 0),
+            (vec![109, 1], Scalar::MAX),
+            (vec![204, 1], Scalar::MAX),
+            (vec![21101, 0, 0, Scalar::MIN], -1),
+        ] {
+            let mut memory = MemoryBank::new(tape.clone());
+            let instruction = Instruction::decode(0, tape[0], &memory).unwrap();
+            assert_eq!(
+                instruction.apply(0, &mut memory, relative_base, &mut input),
+                Err(IntcodeError::ArithmeticOverflow {
+                    pc: 0,
+                    word: tape[0]
+                }),
+                "tape: {:?}",
+                tape
+            );
+        }
+        assert_eq!(Relative(1).address(Scalar::MAX), None);
+    }
+
+    #[test]
+    fn test_instruction_display() {
+        let memory = MemoryBank::new(vec![21002, 4, 3, -1, 99]);
+        let instruction = Instruction::decode(0, 21002, &memory).unwrap();
+        assert_eq!(instruction.to_string(), "MUL 4, #3, @-1");
+        assert_eq!(instruction.parameters().len(), instruction.width() - 1);
+        assert_eq!(Instruction::Halt.to_string(), "HLT");
+    }
+
+    #[test]
+    fn test_instruction_encode() {
+        for tape in [
+            vec![1002, 4, 3, 4],
+            vec![21101, 100, -1, 4],
+            vec![3, 225],
+            vec![1105, 1, 9],
+            vec![209, -7],
+            vec![99],
+        ] {
+            let memory = MemoryBank::new(tape.clone());
+            let instruction = Instruction::decode(0, tape[0], &memory).unwrap();
+            assert_eq!(instruction.encode(), tape);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    io::{stdin, stdout, BufRead, Write},
+    rc::Rc,
+};
+
+use super::memory::Scalar;
+
+/// Source of the values consumed by the `INPUT` instruction.
+pub trait InputDevice {
+    /// Returns `None` when no value is currently available.
+    fn read(&mut self) -> Option<Scalar>;
+
+    /// Values still waiting to be read, for devices that can tell.
+    fn pending(&self) -> Vec<Scalar> {
+        vec![]
+    }
+}
+
+/// Sink of the values emitted by the `OUTPUT` instruction.
+pub trait OutputDevice {
+    fn write(&mut self, value: Scalar);
+
+    /// Values written but not consumed yet, for devices that can tell.
+    fn pending(&self) -> Vec<Scalar> {
+        vec![]
+    }
+}
+
+impl OutputDevice for Vec<Scalar> {
+    fn write(&mut self, value: Scalar) {
+        self.push(value);
+    }
+
+    fn pending(&self) -> Vec<Scalar> {
+        self.clone()
+    }
+}
+
+/// FIFO queue device.
+///
+/// Clones share the same underlying queue, so a handle can be kept to feed
+/// (or drain) a machine after giving it the device.
+#[derive(Clone, Debug, Default)]
+pub struct QueueDevice {
+    queue: Rc<RefCell<VecDeque<Scalar>>>,
+}
+
+impl From<Vec<Scalar>> for QueueDevice {
+    fn from(values: Vec<Scalar>) -> Self {
+        Self {
+            queue: Rc::new(RefCell::new(values.into())),
+        }
+    }
+}
+
+impl QueueDevice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, value: Scalar) {
+        self.queue.borrow_mut().push_back(value);
+    }
+
+    pub fn pop(&self) -> Option<Scalar> {
+        self.queue.borrow_mut().pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.borrow().is_empty()
+    }
+
+    /// Removes and returns every queued value.
+    pub fn drain(&self) -> Vec<Scalar> {
+        self.queue.borrow_mut().drain(..).collect()
+    }
+}
+
+impl InputDevice for QueueDevice {
+    fn read(&mut self) -> Option<Scalar> {
+        self.pop()
+    }
+
+    fn pending(&self) -> Vec<Scalar> {
+        self.queue.borrow().iter().copied().collect()
+    }
+}
+
+impl OutputDevice for QueueDevice {
+    fn write(&mut self, value: Scalar) {
+        self.push(value);
+    }
+
+    fn pending(&self) -> Vec<Scalar> {
+        self.queue.borrow().iter().copied().collect()
+    }
+}
+
+/// Closure-backed device: `FnMut() -> Option<Scalar>` as an input,
+/// `FnMut(Scalar)` as an output.
+pub struct ClosureDevice<F>(pub F);
+
+impl<F> InputDevice for ClosureDevice<F>
+where
+    F: FnMut() -> Option<Scalar>,
+{
+    fn read(&mut self) -> Option<Scalar> {
+        (self.0)()
+    }
+}
+
+impl<F> OutputDevice for ClosureDevice<F>
+where
+    F: FnMut(Scalar),
+{
+    fn write(&mut self, value: Scalar) {
+        (self.0)(value)
+    }
+}
+
+/// Interactive device: prompts on stdin for inputs and prints outputs to stdout.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdioDevice;
+
+impl InputDevice for StdioDevice {
+    /// Returns `None` on end of input or on an unparseable line.
+    fn read(&mut self) -> Option<Scalar> {
+        print!("intcode input> ");
+        stdout().flush().ok()?;
+        let mut line = String::new();
+        stdin().lock().read_line(&mut line).ok()?;
+        line.trim().parse().ok()
+    }
+}
+
+impl OutputDevice for StdioDevice {
+    fn write(&mut self, value: Scalar) {
+        println!("intcode output: {}", value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClosureDevice, InputDevice, OutputDevice, QueueDevice};
+
+    #[test]
+    fn test_queue_device_shared_handle() {
+        let handle = QueueDevice::from(vec![1, 2]);
+        let mut device = handle.clone();
+        assert_eq!(device.read(), Some(1));
+        handle.push(3);
+        device.write(4);
+        assert_eq!(handle.len(), 3);
+        assert_eq!(handle.drain(), vec![2, 3, 4]);
+        assert_eq!(device.read(), None);
+    }
+
+    #[test]
+    fn test_closure_device() {
+        let mut counter = 0;
+        let mut input = ClosureDevice(|| {
+            counter += 1;
+            Some(counter)
+        });
+        assert_eq!(input.read(), Some(1));
+        assert_eq!(input.read(), Some(2));
+
+        let mut written = vec![];
+        let mut output = ClosureDevice(|value| written.push(value));
+        output.write(42);
+        assert_eq!(written, vec![42]);
+    }
+}
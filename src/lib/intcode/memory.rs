@@ -0,0 +1,272 @@
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
+
+use anyhow::{Context, Error, Result};
+
+pub type Scalar = i64;
+
+/// Converts a scalar used as a memory address, returning `None` if negative.
+pub fn scalar_to_address(value: Scalar) -> Option<usize> {
+    usize::try_from(value).ok()
+}
+
+/// Number of words per tape page.
+const PAGE_SIZE: usize = 64;
+
+/// Intcode memory: the loaded tape, followed by an unbounded zero-initialized
+/// region stored sparsely.
+///
+/// The tape is split into pages shared between clones, a page being copied
+/// on its first write. Cloning a bank is therefore cheap, so search-style
+/// solvers can fork a machine state many times.
+#[derive(Clone, Debug)]
+pub struct MemoryBank {
+    tape_len: usize,
+    pages: Vec<Arc<Vec<Scalar>>>,
+    /// Non-zero words written beyond the loaded tape.
+    extended: BTreeMap<usize, Scalar>,
+    /// Structure: (address, old value) of every write since the journal was
+    /// last taken, when journaling is enabled.
+    journal: Option<Vec<(usize, Scalar)>>,
+}
+
+/// Compares memory contents, ignoring the journal.
+impl PartialEq for MemoryBank {
+    fn eq(&self, other: &Self) -> bool {
+        self.tape_len == other.tape_len
+            && self
+                .pages
+                .iter()
+                .zip(&other.pages)
+                .all(|(lhs, rhs)| Arc::ptr_eq(lhs, rhs) || lhs == rhs)
+            && self.extended == other.extended
+    }
+}
+
+impl Eq for MemoryBank {}
+
+impl TryFrom<String> for MemoryBank {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let split = value.split(',');
+        let mut tape = Vec::with_capacity(split.size_hint().0);
+        for part in split {
+            let part_scalar = part
+                .parse()
+                .with_context(|| format!("cannot parse tape scalar: {}", part))?;
+            tape.push(part_scalar);
+        }
+        Ok(Self::new(tape))
+    }
+}
+
+impl MemoryBank {
+    /// Loads a comma-separated tape from a file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("cannot read tape file: {}", path.display()))?;
+        Self::try_from(content.trim().to_string())
+    }
+
+    pub fn new(tape: Vec<Scalar>) -> Self {
+        Self {
+            tape_len: tape.len(),
+            pages: tape
+                .chunks(PAGE_SIZE)
+                .map(|page| Arc::new(page.to_vec()))
+                .collect(),
+            extended: BTreeMap::new(),
+            journal: None,
+        }
+    }
+
+    /// Reads zero for any address never written beyond the loaded tape.
+    pub fn get_scalar_at(&self, index: usize) -> Scalar {
+        if index < self.tape_len {
+            self.pages[index / PAGE_SIZE][index % PAGE_SIZE]
+        } else {
+            self.extended.get(&index).copied().unwrap_or(0)
+        }
+    }
+
+    pub fn set_scalar_at(&mut self, index: usize, value: Scalar) {
+        let old = self.get_scalar_at(index);
+        if let Some(journal) = self.journal.as_mut() {
+            journal.push((index, old));
+        }
+        self.write(index, value);
+    }
+
+    fn write(&mut self, index: usize, value: Scalar) {
+        if index < self.tape_len {
+            Arc::make_mut(&mut self.pages[index / PAGE_SIZE])[index % PAGE_SIZE] = value;
+        } else if value == 0 {
+            self.extended.remove(&index);
+        } else {
+            self.extended.insert(index, value);
+        }
+    }
+
+    /// Copy of the loaded tape, excluding any memory written beyond it.
+    pub fn raw(&self) -> Vec<Scalar> {
+        self.pages
+            .iter()
+            .flat_map(|page| page.iter().copied())
+            .collect()
+    }
+
+    /// Length of the loaded tape.
+    pub fn tape_len(&self) -> usize {
+        self.tape_len
+    }
+
+    /// Number of tape pages not shared with any clone of this bank.
+    pub fn owned_pages(&self) -> usize {
+        self.pages
+            .iter()
+            .filter(|page| Arc::strong_count(page) == 1)
+            .count()
+    }
+
+    /// Non-zero words written beyond the loaded tape, by address.
+    pub fn extended(&self) -> &BTreeMap<usize, Scalar> {
+        &self.extended
+    }
+
+    /// Starts recording the old value of every word written.
+    pub fn enable_journal(&mut self) {
+        self.journal.get_or_insert_with(Vec::new);
+    }
+
+    /// Stops recording writes, dropping the ones not taken yet.
+    pub fn disable_journal(&mut self) {
+        self.journal = None;
+    }
+
+    /// Returns the writes recorded since the last call, oldest first.
+    ///
+    /// Always empty when journaling is disabled.
+    pub fn take_journal(&mut self) -> Vec<(usize, Scalar)> {
+        self.journal
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Writes recorded since the last call to `take_journal`, oldest first.
+    pub fn pending_journal(&self) -> &[(usize, Scalar)] {
+        self.journal.as_deref().unwrap_or_default()
+    }
+
+    /// Drops the writes recorded since the last call to `take_journal`.
+    pub fn clear_journal(&mut self) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.clear();
+        }
+    }
+
+    /// Reverts writes returned by `take_journal`, without journaling them.
+    pub fn undo(&mut self, journal: &[(usize, Scalar)]) {
+        for &(index, old) in journal.iter().rev() {
+            self.write(index, old);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scalar_to_address, MemoryBank};
+
+    #[test]
+    fn test_memory_bank_signed_parsing() {
+        let memory = MemoryBank::try_from("1101,100,-1,4,0".to_string()).unwrap();
+        assert_eq!(memory.raw(), &[1101, 100, -1, 4, 0]);
+        assert!(MemoryBank::try_from("1,2,x".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_memory_bank_beyond_tape() {
+        let mut memory = MemoryBank::new(vec![1, 2, 3]);
+        assert_eq!(memory.get_scalar_at(3), 0);
+        assert_eq!(memory.get_scalar_at(1 << 40), 0);
+
+        memory.set_scalar_at(1 << 40, 42);
+        memory.set_scalar_at(5, 7);
+        assert_eq!(memory.get_scalar_at(1 << 40), 42);
+        assert_eq!(memory.get_scalar_at(5), 7);
+        assert_eq!(memory.raw(), &[1, 2, 3]);
+        assert_eq!(memory.extended().len(), 2);
+
+        memory.set_scalar_at(5, 0);
+        assert_eq!(memory.get_scalar_at(5), 0);
+        assert_eq!(memory.extended().len(), 1);
+    }
+
+    #[test]
+    fn test_memory_bank_copy_on_write() {
+        let tape: Vec<i64> = (0..200).collect();
+        let base = MemoryBank::new(tape.clone());
+        assert_eq!(base.tape_len(), 200);
+        assert_eq!(base.owned_pages(), 4);
+
+        let mut fork = base.clone();
+        assert_eq!(base.owned_pages(), 0);
+        fork.set_scalar_at(1, -1);
+        fork.set_scalar_at(2, -2);
+        fork.set_scalar_at(500, -3);
+        assert_eq!(fork.owned_pages(), 1);
+        assert_eq!(base.owned_pages(), 1);
+
+        assert_eq!(base.raw(), tape);
+        assert_ne!(base, fork);
+        fork.set_scalar_at(1, 1);
+        fork.set_scalar_at(2, 2);
+        fork.set_scalar_at(500, 0);
+        assert_eq!(base, fork);
+        fork.set_scalar_at(1, -1);
+        fork.set_scalar_at(2, -2);
+        fork.set_scalar_at(500, -3);
+        assert_eq!(base.get_scalar_at(500), 0);
+        assert_eq!(fork.get_scalar_at(1), -1);
+        assert_eq!(fork.get_scalar_at(199), 199);
+        assert_eq!(fork.get_scalar_at(500), -3);
+        assert_eq!(fork.raw()[..4], [0, -1, -2, 3]);
+
+        drop(base);
+        assert_eq!(fork.owned_pages(), 4);
+    }
+
+    #[test]
+    fn test_memory_bank_journal() {
+        let mut memory = MemoryBank::new(vec![1, 2, 3]);
+        memory.set_scalar_at(0, 10);
+        assert_eq!(memory.take_journal(), vec![]);
+
+        memory.enable_journal();
+        memory.set_scalar_at(1, 20);
+        memory.set_scalar_at(1, 30);
+        memory.set_scalar_at(8, 40);
+        let journal = memory.take_journal();
+        assert_eq!(journal, vec![(1, 2), (1, 20), (8, 0)]);
+        assert_eq!(memory.take_journal(), vec![]);
+
+        memory.undo(&journal);
+        assert_eq!(memory.raw(), &[10, 2, 3]);
+        assert!(memory.extended().is_empty());
+        assert_eq!(memory.take_journal(), vec![]);
+
+        memory.set_scalar_at(2, 50);
+        memory.disable_journal();
+        assert!(memory.pending_journal().is_empty());
+        memory.set_scalar_at(2, 60);
+        assert_eq!(memory.take_journal(), vec![]);
+    }
+
+    #[test]
+    fn test_scalar_to_address() {
+        assert_eq!(scalar_to_address(0), Some(0));
+        assert_eq!(scalar_to_address(42), Some(42));
+        assert_eq!(scalar_to_address(-1), None);
+    }
+}
@@ -0,0 +1,39 @@
+//! Shared Intcode engine, used by every Intcode-based day.
+
+mod assembler;
+mod debugger;
+mod decode_cache;
+mod disassembler;
+mod error;
+mod flow_graph;
+mod instruction;
+mod io;
+mod memory;
+mod profile;
+mod scheduler;
+mod search;
+mod self_modification;
+mod snapshot;
+mod symbolic;
+mod trace;
+mod translate;
+mod vm;
+
+pub use assembler::{assemble, pretty_print};
+pub use debugger::{Debugger, DebuggerStop};
+pub use decode_cache::DecodeCache;
+pub use disassembler::{disassemble, render_disassembly, Disassembled, DisassembledLine};
+pub use error::{IntcodeError, IntcodeResult};
+pub use flow_graph::{BasicBlock, BlockExit, ControlFlowGraph};
+pub use instruction::{Effect, Instruction, InstructionMode, Parameter};
+pub use io::{ClosureDevice, InputDevice, OutputDevice, QueueDevice, StdioDevice};
+pub use memory::{scalar_to_address, MemoryBank, Scalar};
+pub use profile::{enable_global_profiling, take_global_profile, Profile};
+pub use scheduler::{Scheduler, SchedulerError};
+pub use search::ParameterSearch;
+pub use self_modification::{CodeWrite, SelfModification};
+pub use snapshot::Snapshot;
+pub use symbolic::{symbolic_result, Polynomial};
+pub use trace::{TraceFormat, TraceRecord, TraceRecorder, TraceWriter, Tracer};
+pub use translate::{translate, TranslatedRuntime};
+pub use vm::{RunStatus, VirtualMachine};
@@ -0,0 +1,244 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use super::instruction::Instruction;
+
+/// Number of addresses listed in the hot-spot report.
+const PROFILE_REPORT_HOT_SPOTS: usize = 10;
+
+/// Execution statistics of one or several `VirtualMachine` runs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Number of executed instructions.
+    pub cycles: u64,
+    /// Number of values read by `IN` instructions.
+    pub inputs: u64,
+    /// Number of values written by `OUT` instructions.
+    pub outputs: u64,
+    /// Executions by mnemonic.
+    pub by_opcode: BTreeMap<&'static str, u64>,
+    /// Structure: address -> (executions, last instruction executed there)
+    pub by_address: BTreeMap<usize, (u64, Instruction)>,
+}
+
+impl Profile {
+    pub fn record(&mut self, pc: usize, instruction: Instruction) {
+        self.cycles += 1;
+        match instruction {
+            Instruction::Input(_) => self.inputs += 1,
+            Instruction::Output(_) => self.outputs += 1,
+            _ => {}
+        }
+        *self.by_opcode.entry(instruction.mnemonic()).or_insert(0) += 1;
+        let entry = self.by_address.entry(pc).or_insert((0, instruction));
+        *entry = (entry.0 + 1, instruction);
+    }
+
+    pub fn merge(&mut self, other: &Profile) {
+        self.cycles += other.cycles;
+        self.inputs += other.inputs;
+        self.outputs += other.outputs;
+        for (mnemonic, count) in &other.by_opcode {
+            *self.by_opcode.entry(mnemonic).or_insert(0) += count;
+        }
+        for (&address, &(count, instruction)) in &other.by_address {
+            let entry = self.by_address.entry(address).or_insert((0, instruction));
+            *entry = (entry.0 + count, instruction);
+        }
+    }
+
+    /// The `count` most executed addresses, most executed first.
+    ///
+    /// Structure: (address, executions, last instruction executed there)
+    pub fn hot_spots(&self, count: usize) -> Vec<(usize, u64, Instruction)> {
+        let mut hot_spots: Vec<_> = self
+            .by_address
+            .iter()
+            .map(|(&address, &(executions, instruction))| (address, executions, instruction))
+            .collect();
+        hot_spots.sort_by_key(|&(address, executions, _)| (Reverse(executions), address));
+        hot_spots.truncate(count);
+        hot_spots
+    }
+}
+
+fn percentage(count: u64, total: u64) -> f64 {
+    100.0 * count as f64 / total.max(1) as f64
+}
+
+/// Hot-spot report: totals, executions by opcode, then the most executed
+/// addresses.
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} instructions executed, {} inputs, {} outputs",
+            self.cycles, self.inputs, self.outputs
+        )?;
+        let mut by_opcode: Vec<_> = self.by_opcode.iter().collect();
+        by_opcode.sort_by_key(|&(mnemonic, count)| (Reverse(*count), *mnemonic));
+        writeln!(f, "by opcode:")?;
+        for (mnemonic, count) in by_opcode {
+            writeln!(
+                f,
+                "  {:<4} {:>12} {:>6.1}%",
+                mnemonic,
+                count,
+                percentage(*count, self.cycles)
+            )?;
+        }
+        writeln!(f, "hot spots:")?;
+        for (address, count, instruction) in self.hot_spots(PROFILE_REPORT_HOT_SPOTS) {
+            writeln!(
+                f,
+                "  {:>5}: {:<24} {:>12} {:>6.1}%",
+                address,
+                instruction.to_string(),
+                count,
+                percentage(count, self.cycles)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Profile shared by every `VirtualMachine` while enabled.
+struct GlobalProfile {
+    /// Checked without locking, as every machine does when created.
+    enabled: AtomicBool,
+    profile: Mutex<Option<Profile>>,
+}
+
+impl GlobalProfile {
+    const fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            profile: Mutex::new(None),
+        }
+    }
+
+    fn enable(&self) {
+        *self.profile.lock().unwrap() = Some(Profile::default());
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    fn merge(&self, profile: &Profile) {
+        if let Some(global) = self.profile.lock().unwrap().as_mut() {
+            global.merge(profile);
+        }
+    }
+
+    fn take(&self) -> Option<Profile> {
+        self.enabled.store(false, Ordering::Relaxed);
+        self.profile.lock().unwrap().take()
+    }
+}
+
+static GLOBAL_PROFILE: GlobalProfile = GlobalProfile::new();
+
+/// Makes every `VirtualMachine` created from now on profile itself, and add
+/// its statistics to a process-wide profile once dropped.
+pub fn enable_global_profiling() {
+    GLOBAL_PROFILE.enable();
+}
+
+pub(super) fn global_profiling_enabled() -> bool {
+    GLOBAL_PROFILE.enabled()
+}
+
+pub(super) fn merge_into_global_profile(profile: &Profile) {
+    GLOBAL_PROFILE.merge(profile);
+}
+
+/// Stops global profiling, returning the statistics gathered since it was
+/// enabled.
+pub fn take_global_profile() -> Option<Profile> {
+    GLOBAL_PROFILE.take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GlobalProfile, Profile};
+    use crate::intcode::{assemble, QueueDevice, VirtualMachine};
+
+    #[test]
+    fn test_profile_counts() {
+        let tape = assemble(
+            "
+            loop: in 100
+                  out 100
+                  jt 100, #loop
+                  hlt
+        ",
+        )
+        .unwrap();
+        let mut vm = VirtualMachine::from_tape(&tape)
+            .with_input(QueueDevice::from(vec![3, 2, 0]))
+            .with_profiling();
+        assert_eq!(vm.run_until_halt().unwrap(), vec![3, 2, 0]);
+
+        let profile = vm.profile().unwrap().clone();
+        assert_eq!(
+            (profile.cycles, profile.inputs, profile.outputs),
+            (10, 3, 3)
+        );
+        assert_eq!(profile.by_opcode.get("JT"), Some(&3));
+        assert_eq!(profile.by_opcode.get("HLT"), Some(&1));
+        let hot_spots: Vec<(usize, u64)> = profile
+            .hot_spots(2)
+            .into_iter()
+            .map(|(address, count, _)| (address, count))
+            .collect();
+        assert_eq!(hot_spots, vec![(0, 3), (2, 3)]);
+
+        let mut merged = Profile::default();
+        merged.merge(&profile);
+        merged.merge(&profile);
+        assert_eq!(merged.cycles, 20);
+        assert_eq!(merged.by_address.get(&7).map(|entry| entry.0), Some(2));
+
+        let report = profile.to_string();
+        assert!(report.starts_with("10 instructions executed, 3 inputs, 3 outputs\n"));
+        assert!(report.contains("  JT              3   30.0%\n"));
+        assert!(report.contains("      4: JT 100, #0                          3   30.0%\n"));
+    }
+
+    #[test]
+    fn test_profile_disabled_by_default() {
+        let mut vm = VirtualMachine::from_tape(&[99]);
+        vm.run().unwrap();
+        assert!(vm.profile().is_none());
+    }
+
+    #[test]
+    fn test_global_profile() {
+        let global = GlobalProfile::new();
+        let mut vm = VirtualMachine::from_tape(&[1101, 1, 1, 0, 99]).with_profiling();
+        vm.run().unwrap();
+        let profile = vm.profile().unwrap();
+        global.merge(profile);
+        assert!(!global.enabled());
+        assert_eq!(global.take(), None);
+
+        global.enable();
+        assert!(global.enabled());
+        global.merge(profile);
+        global.merge(profile);
+        let merged = global.take().unwrap();
+        assert_eq!(merged.cycles, 4);
+        assert_eq!(merged.by_opcode.get("ADD"), Some(&2));
+        assert!(!global.enabled());
+        assert_eq!(global.take(), None);
+    }
+}
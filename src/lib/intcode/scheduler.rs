@@ -0,0 +1,221 @@
+use std::{collections::BTreeMap, error::Error, fmt};
+
+use super::{
+    error::IntcodeError,
+    io::QueueDevice,
+    vm::{RunStatus, VirtualMachine},
+};
+
+/// Failures of a `Scheduler` run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchedulerError {
+    /// A machine failed.
+    Machine { name: String, error: IntcodeError },
+    /// Every machine still running is blocked on an empty input channel.
+    ///
+    /// Structure: (machine name, program counter), in scheduling order
+    Deadlock { blocked: Vec<(String, usize)> },
+}
+
+impl fmt::Display for SchedulerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedulerError::Machine { name, error } => write!(f, "machine {}: {}", name, error),
+            SchedulerError::Deadlock { blocked } => {
+                let blocked: Vec<String> = blocked
+                    .iter()
+                    .map(|(name, pc)| format!("{} waits for input at {}", name, pc))
+                    .collect();
+                write!(f, "deadlock: {}", blocked.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for SchedulerError {}
+
+struct ScheduledMachine {
+    name: String,
+    vm: VirtualMachine,
+    halted: bool,
+}
+
+/// Runs several machines connected by named channels, e.g. amplifier chains
+/// and feedback loops.
+///
+/// Machines take turns in the order they were added, each one running until
+/// it outputs a value, blocks on input or halts.
+#[derive(Default)]
+pub struct Scheduler {
+    machines: Vec<ScheduledMachine>,
+    channels: BTreeMap<String, QueueDevice>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle on the channel called `name`, created empty if needed, e.g. to
+    /// feed it initial values or drain it once the machines halted.
+    pub fn channel(&mut self, name: &str) -> QueueDevice {
+        self.channels.entry(name.to_string()).or_default().clone()
+    }
+
+    /// Adds `vm` as `name`, reading from the `input` channel and writing to
+    /// the `output` channel. Its own I/O devices are replaced.
+    pub fn machine(mut self, name: &str, vm: VirtualMachine, input: &str, output: &str) -> Self {
+        let vm = vm
+            .with_input(self.channel(input))
+            .with_output(self.channel(output));
+        self.machines.push(ScheduledMachine {
+            name: name.to_string(),
+            vm,
+            halted: false,
+        });
+        self
+    }
+
+    /// The first machine added as `name`, e.g. to inspect its memory.
+    pub fn vm(&self, name: &str) -> Option<&VirtualMachine> {
+        self.machines
+            .iter()
+            .find(|machine| machine.name == name)
+            .map(|machine| &machine.vm)
+    }
+
+    /// Runs the machines round-robin until all of them halt.
+    ///
+    /// Fails as soon as a machine fails, or once a whole round went by
+    /// without any machine executing an instruction.
+    pub fn run(&mut self) -> Result<(), SchedulerError> {
+        loop {
+            let mut running = false;
+            let mut progressed = false;
+            for machine in self.machines.iter_mut().filter(|machine| !machine.halted) {
+                running = true;
+                let cycles = machine.vm.cycles();
+                let status = machine.vm.run().map_err(|error| SchedulerError::Machine {
+                    name: machine.name.clone(),
+                    error,
+                })?;
+                machine.halted = status == RunStatus::Halted;
+                progressed |= machine.vm.cycles() != cycles;
+            }
+            if !running {
+                return Ok(());
+            }
+            if !progressed {
+                return Err(SchedulerError::Deadlock {
+                    blocked: self
+                        .machines
+                        .iter()
+                        .filter(|machine| !machine.halted)
+                        .map(|machine| {
+                            (machine.name.clone(), machine.vm.program_counter_snapshot())
+                        })
+                        .collect(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scheduler, SchedulerError};
+    use crate::intcode::{IntcodeError, VirtualMachine};
+
+    const AMPLIFIERS: [&str; 5] = ["A", "B", "C", "D", "E"];
+
+    /// Chains one amplifier per phase setting, the last one feeding the
+    /// first one if `feedback`, and returns the last signal it output.
+    ///
+    /// Channels are named after the amplifier they feed.
+    fn amplify(tape: &[i64], phases: &[i64], feedback: bool) -> i64 {
+        let mut scheduler = Scheduler::new();
+        for (name, &phase) in AMPLIFIERS.iter().zip(phases) {
+            scheduler.channel(name).push(phase);
+        }
+        scheduler.channel("A").push(0);
+        for (i, name) in AMPLIFIERS.iter().enumerate().take(phases.len()) {
+            let output = match (i + 1 < phases.len(), feedback) {
+                (true, _) => AMPLIFIERS[i + 1],
+                (false, true) => "A",
+                (false, false) => "signal",
+            };
+            scheduler = scheduler.machine(name, VirtualMachine::from_tape(tape), name, output);
+        }
+        scheduler.run().unwrap();
+        let output = if feedback { "A" } else { "signal" };
+        *scheduler.channel(output).drain().last().unwrap()
+    }
+
+    #[test]
+    fn test_scheduler_amplifier_chain() {
+        let tape = [
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ];
+        assert_eq!(amplify(&tape, &[4, 3, 2, 1, 0], false), 43210);
+    }
+
+    #[test]
+    fn test_scheduler_feedback_loop() {
+        let tape = [
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ];
+        assert_eq!(amplify(&tape, &[9, 8, 7, 6, 5], true), 139629729);
+    }
+
+    #[test]
+    fn test_scheduler_errors() {
+        // both machines need two inputs before answering
+        let tape = [3, 7, 3, 7, 4, 7, 99, 0];
+        let mut scheduler = Scheduler::new()
+            .machine(
+                "ping",
+                VirtualMachine::from_tape(&tape),
+                "to ping",
+                "to pong",
+            )
+            .machine(
+                "pong",
+                VirtualMachine::from_tape(&tape),
+                "to pong",
+                "to ping",
+            );
+        scheduler.channel("to ping").push(1);
+        let error = scheduler.run().unwrap_err();
+        assert_eq!(
+            error,
+            SchedulerError::Deadlock {
+                blocked: vec![("ping".to_string(), 2), ("pong".to_string(), 0)]
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "deadlock: ping waits for input at 2, pong waits for input at 0"
+        );
+
+        // halted machines are left out
+        let mut scheduler = Scheduler::new()
+            .machine("halts", VirtualMachine::from_tape(&[99]), "in", "out")
+            .machine("waits", VirtualMachine::from_tape(&[3, 0, 99]), "in", "out");
+        assert!(matches!(
+            scheduler.run(),
+            Err(SchedulerError::Deadlock { blocked }) if blocked == vec![("waits".to_string(), 0)]
+        ));
+        assert_eq!(scheduler.vm("halts").unwrap().program_counter_snapshot(), 0);
+
+        let mut scheduler =
+            Scheduler::new().machine("broken", VirtualMachine::from_tape(&[42]), "in", "out");
+        assert_eq!(
+            scheduler.run(),
+            Err(SchedulerError::Machine {
+                name: "broken".to_string(),
+                error: IntcodeError::UnknownOpcode { pc: 0, word: 42 }
+            })
+        );
+    }
+}
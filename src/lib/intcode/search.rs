@@ -0,0 +1,274 @@
+use std::{
+    ops::Range,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use super::{
+    decode_cache::DecodeCache,
+    error::{IntcodeError, IntcodeResult},
+    memory::{MemoryBank, Scalar},
+    vm::VirtualMachine,
+};
+
+/// Outcome of one worker: the indices of its matching points, in increasing
+/// order, and the error that stopped it early, if any.
+type WorkerOutcome = (Vec<usize>, Option<(usize, IntcodeError)>);
+
+/// Parallel search over the inputs of a program.
+///
+/// Every point of the space patches the parameter addresses of a fork of the
+/// memory, runs it until it halts, then compares the word at the
+/// result address (0 by default) to a target. Points are ordered
+/// lexicographically, the first parameter varying the slowest.
+#[derive(Clone, Debug)]
+pub struct ParameterSearch {
+    memory: MemoryBank,
+    /// Structure: (address, values)
+    parameters: Vec<(usize, Range<Scalar>)>,
+    result_address: usize,
+    threads: usize,
+    step_limit: Option<u64>,
+    decode_cache: Option<DecodeCache>,
+}
+
+impl ParameterSearch {
+    /// Uses one thread per available CPU.
+    pub fn new(memory: MemoryBank) -> Self {
+        Self {
+            memory,
+            parameters: vec![],
+            result_address: 0,
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            step_limit: None,
+            decode_cache: None,
+        }
+    }
+
+    /// Adds a dimension to the space: the word at `address` takes every value
+    /// of `values`.
+    pub fn parameter(mut self, address: usize, values: Range<Scalar>) -> Self {
+        self.parameters.push((address, values));
+        self
+    }
+
+    pub fn result_address(mut self, address: usize) -> Self {
+        self.result_address = address;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Makes runs fail instead of executing more than `limit` instructions,
+    /// see `VirtualMachine::with_step_limit`.
+    pub fn step_limit(mut self, limit: u64) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    /// Decodes the program once for every run, see
+    /// `VirtualMachine::with_decode_cache`.
+    pub fn decode_cache(mut self) -> Self {
+        self.decode_cache = Some(DecodeCache::new(&self.memory));
+        self
+    }
+
+    /// Number of points in the space.
+    pub fn len(&self) -> usize {
+        self.parameters
+            .iter()
+            .map(|(_, values)| values.end.saturating_sub(values.start) as usize)
+            .product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Parameter values of the point at `index`.
+    fn point(&self, mut index: usize) -> Vec<Scalar> {
+        let mut point = vec![0; self.parameters.len()];
+        for (value, (_, values)) in point.iter_mut().zip(&self.parameters).rev() {
+            let size = (values.end - values.start) as usize;
+            *value = values.start + (index % size) as Scalar;
+            index /= size;
+        }
+        point
+    }
+
+    fn matches(&self, index: usize, target: Scalar) -> IntcodeResult<bool> {
+        let mut memory = self.memory.clone();
+        let mut cache = self.decode_cache.clone();
+        for (value, (address, _)) in self.point(index).into_iter().zip(&self.parameters) {
+            memory.set_scalar_at(*address, value);
+            if let Some(cache) = cache.as_mut() {
+                cache.invalidate(*address);
+            }
+        }
+        let mut vm = VirtualMachine::from_memory(memory);
+        if let Some(cache) = cache {
+            vm = vm.with_shared_decode_cache(cache);
+        }
+        if let Some(limit) = self.step_limit {
+            vm = vm.with_step_limit(limit);
+        }
+        vm.run_until_halt()?;
+        Ok(vm.memory_snapshot().get_scalar_at(self.result_address) == target)
+    }
+
+    /// Splits the points between the threads, each one checking every
+    /// `threads`-th point in increasing order.
+    ///
+    /// With `stop_at_first`, workers give up on points past the first match
+    /// or error found by any of them.
+    fn run_workers(&self, target: Scalar, stop_at_first: bool) -> Vec<WorkerOutcome> {
+        let len = self.len();
+        let threads = self.threads.min(len).max(1);
+        let stop_at = AtomicUsize::new(usize::MAX);
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|worker| {
+                    let stop_at = &stop_at;
+                    scope.spawn(move || {
+                        let mut found = vec![];
+                        for index in (worker..len).step_by(threads) {
+                            if index > stop_at.load(Ordering::Relaxed) {
+                                break;
+                            }
+                            match self.matches(index, target) {
+                                Ok(false) => {}
+                                Ok(true) => {
+                                    found.push(index);
+                                    if stop_at_first {
+                                        stop_at.fetch_min(index, Ordering::Relaxed);
+                                    }
+                                }
+                                Err(error) => {
+                                    stop_at.fetch_min(index, Ordering::Relaxed);
+                                    return (found, Some((index, error)));
+                                }
+                            }
+                        }
+                        (found, None)
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("search worker panicked"))
+                .collect()
+        })
+    }
+
+    /// Returns the first point for which the result word equals `target`.
+    ///
+    /// Fails if a run errors on an earlier point.
+    pub fn find_first(&self, target: Scalar) -> IntcodeResult<Option<Vec<Scalar>>> {
+        let outcomes = self.run_workers(target, true);
+        let first_match = outcomes.iter().filter_map(|(found, _)| found.first()).min();
+        let first_error = outcomes
+            .iter()
+            .filter_map(|(_, error)| *error)
+            .min_by_key(|(index, _)| *index);
+        match (first_match, first_error) {
+            (Some(index), Some((error_index, _))) if *index < error_index => {
+                Ok(Some(self.point(*index)))
+            }
+            (_, Some((_, error))) => Err(error),
+            (Some(index), None) => Ok(Some(self.point(*index))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Returns every point for which the result word equals `target`, in order.
+    ///
+    /// Fails if any run errors.
+    pub fn find_all(&self, target: Scalar) -> IntcodeResult<Vec<Vec<Scalar>>> {
+        let outcomes = self.run_workers(target, false);
+        if let Some((_, error)) = outcomes
+            .iter()
+            .filter_map(|(_, error)| *error)
+            .min_by_key(|(index, _)| *index)
+        {
+            return Err(error);
+        }
+        let mut found: Vec<usize> = outcomes.into_iter().flat_map(|(found, _)| found).collect();
+        found.sort_unstable();
+        Ok(found.into_iter().map(|index| self.point(index)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParameterSearch;
+    use crate::intcode::{IntcodeError, MemoryBank, VirtualMachine};
+
+    /// Adds the words at the noun and verb addresses into address 0.
+    const TAPE: [i64; 12] = [1, 0, 0, 0, 99, 5, 6, 7, 8, 9, 10, 11];
+
+    #[test]
+    fn test_parameter_search_matches_brute_force() {
+        let mut expected = vec![];
+        for noun in 0..12 {
+            for verb in 0..12 {
+                let mut vm = VirtualMachine::from_tape(&TAPE);
+                vm.memory_mut().set_scalar_at(1, noun);
+                vm.memory_mut().set_scalar_at(2, verb);
+                vm.run().unwrap();
+                if vm.memory_snapshot().get_scalar_at(0) == 15 {
+                    expected.push(vec![noun, verb]);
+                }
+            }
+        }
+        assert_eq!(expected.len(), 6);
+
+        for threads in [1, 3, 8, 1000] {
+            let search = ParameterSearch::new(MemoryBank::new(TAPE.to_vec()))
+                .parameter(1, 0..12)
+                .parameter(2, 0..12)
+                .threads(threads);
+            assert_eq!(search.len(), 144);
+            assert_eq!(search.find_all(15).unwrap(), expected);
+            assert_eq!(search.find_first(15).unwrap(), Some(expected[0].clone()));
+            assert_eq!(search.find_first(1000).unwrap(), None);
+            let search = search.decode_cache();
+            assert_eq!(search.find_all(15).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_parameter_search_result_address_and_errors() {
+        // MUL #noun, #verb, 5
+        let search = ParameterSearch::new(MemoryBank::new(vec![1102, 0, 0, 5, 99, 0]))
+            .parameter(1, -3..4)
+            .parameter(2, 2..3)
+            .result_address(5);
+        assert_eq!(search.find_all(-4).unwrap(), vec![vec![-2, 2]]);
+        assert_eq!(search.find_first(6).unwrap(), Some(vec![3, 2]));
+
+        // the opcode itself is searched: 1 and 2 halt, 3 (input) fails
+        let search = ParameterSearch::new(MemoryBank::new(vec![0, 0, 0, 0, 99]))
+            .parameter(0, 1..4)
+            .result_address(4);
+        assert_eq!(search.find_first(99).unwrap(), Some(vec![1]));
+        assert!(matches!(
+            search.find_all(99),
+            Err(IntcodeError::InputExhausted { .. })
+        ));
+        let search = search.decode_cache();
+        assert_eq!(search.find_first(99).unwrap(), Some(vec![1]));
+
+        // JT #1, #0 spins forever
+        let search = ParameterSearch::new(MemoryBank::new(vec![1105, 1, 0, 99]))
+            .parameter(1, 0..2)
+            .step_limit(100);
+        assert_eq!(search.find_first(1105).unwrap(), Some(vec![0]));
+        assert!(matches!(
+            search.find_all(1105),
+            Err(IntcodeError::StepLimitExceeded { limit: 100, .. })
+        ));
+    }
+}
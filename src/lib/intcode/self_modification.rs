@@ -0,0 +1,118 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+/// A write into a word executed as part of an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CodeWrite {
+    /// Address of the writing instruction.
+    pub writer: usize,
+    /// Written address.
+    pub target: usize,
+    /// Whether the target was executed after the write, rather than before:
+    /// only such writes change the behavior of the program.
+    pub executed_after: bool,
+}
+
+/// Tracks the words executed as code by a `VirtualMachine`, and the writes
+/// into them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelfModification {
+    /// Addresses of every word belonging to an executed instruction.
+    executed: BTreeSet<usize>,
+    /// Words written since they were last executed, if ever.
+    ///
+    /// Structure: written address -> address of its last writer
+    written: BTreeMap<usize, usize>,
+    writes: BTreeSet<CodeWrite>,
+}
+
+impl SelfModification {
+    /// Records the execution of the instruction of `width` words at `pc`.
+    pub(super) fn record_execution(&mut self, pc: usize, width: usize) {
+        for address in pc..pc + width {
+            self.executed.insert(address);
+            if let Some(writer) = self.written.remove(&address) {
+                self.writes.insert(CodeWrite {
+                    writer,
+                    target: address,
+                    executed_after: true,
+                });
+            }
+        }
+    }
+
+    /// Records a write by the instruction at `writer`.
+    pub(super) fn record_write(&mut self, writer: usize, target: usize) {
+        if self.executed.contains(&target) {
+            self.writes.insert(CodeWrite {
+                writer,
+                target,
+                executed_after: false,
+            });
+        }
+        self.written.insert(target, writer);
+    }
+
+    /// Writes into code found so far, ordered by writer then target.
+    pub fn writes(&self) -> &BTreeSet<CodeWrite> {
+        &self.writes
+    }
+
+    /// Whether an executed instruction includes the word at `address`.
+    pub fn is_code(&self, address: usize) -> bool {
+        self.executed.contains(&address)
+    }
+
+    /// Contiguous ranges of words that belong to executed instructions, in
+    /// increasing order.
+    pub fn code_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = vec![];
+        for &address in &self.executed {
+            match ranges.last_mut() {
+                Some(range) if range.end == address => range.end += 1,
+                _ => ranges.push(address..address + 1),
+            }
+        }
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CodeWrite, SelfModification};
+
+    #[test]
+    fn test_self_modification() {
+        let mut detector = SelfModification::default();
+        detector.record_execution(0, 4);
+        // into the operand of an executed instruction
+        detector.record_write(0, 3);
+        // into data, then into an instruction executed later
+        detector.record_write(4, 20);
+        detector.record_write(4, 9);
+        detector.record_execution(4, 4);
+        detector.record_execution(8, 2);
+
+        assert_eq!(
+            detector.writes().iter().copied().collect::<Vec<_>>(),
+            vec![
+                CodeWrite {
+                    writer: 0,
+                    target: 3,
+                    executed_after: false
+                },
+                CodeWrite {
+                    writer: 4,
+                    target: 9,
+                    executed_after: true
+                },
+            ]
+        );
+        assert!(detector.is_code(3));
+        assert!(detector.is_code(9));
+        assert!(!detector.is_code(20));
+        assert_eq!(detector.code_ranges(), vec![0..10]);
+    }
+}
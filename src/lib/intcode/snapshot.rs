@@ -0,0 +1,225 @@
+//! Saved `VirtualMachine` state.
+//!
+//! Snapshots are stored as text, one `key value` line per field, in this
+//! order:
+//!
+//! ```text
+//! intcode-snapshot 1          ; format version
+//! program-counter 12
+//! relative-base 0
+//! cycles 42                   ; instructions executed so far
+//! tape 1,2,3,4,99             ; memory covered by the loaded tape
+//! extended 1000=5,1003=-7     ; non-zero words beyond the tape, `address=value`
+//! input 5,6                   ; values not yet read by the program
+//! output 7                    ; values output but not yet consumed
+//! ```
+//!
+//! Lists may be empty, in which case nothing follows the key.
+
+use std::{collections::BTreeMap, fmt, fs, path::Path, str::FromStr};
+
+use anyhow::{anyhow, Context, Error, Result};
+
+use super::memory::{MemoryBank, Scalar};
+
+const SNAPSHOT_MAGIC: &str = "intcode-snapshot";
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    pub program_counter: usize,
+    pub relative_base: Scalar,
+    pub cycles: u64,
+    pub tape: Vec<Scalar>,
+    /// Non-zero words written beyond the tape, by address.
+    pub extended: BTreeMap<usize, Scalar>,
+    /// Pending input, only captured from devices able to report it.
+    pub input: Vec<Scalar>,
+    /// Pending output, only captured from devices able to report it.
+    pub output: Vec<Scalar>,
+}
+
+impl Snapshot {
+    /// State of a machine that just loaded `tape`.
+    pub fn from_tape(tape: &[Scalar]) -> Self {
+        Self {
+            tape: tape.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    /// Rebuilds the memory described by the snapshot.
+    pub fn memory(&self) -> MemoryBank {
+        let mut memory = MemoryBank::new(self.tape.clone());
+        for (&address, &value) in &self.extended {
+            memory.set_scalar_at(address, value);
+        }
+        memory
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("cannot read snapshot file: {}", path.display()))?;
+        content
+            .parse()
+            .with_context(|| format!("invalid snapshot file: {}", path.display()))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string())
+            .with_context(|| format!("cannot write snapshot file: {}", path.display()))
+    }
+}
+
+fn join<T: fmt::Display>(values: impl Iterator<Item = T>) -> String {
+    values
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+        writeln!(f, "program-counter {}", self.program_counter)?;
+        writeln!(f, "relative-base {}", self.relative_base)?;
+        writeln!(f, "cycles {}", self.cycles)?;
+        writeln!(f, "tape {}", join(self.tape.iter()))?;
+        let extended = self
+            .extended
+            .iter()
+            .map(|(address, value)| format!("{}={}", address, value));
+        writeln!(f, "extended {}", join(extended))?;
+        writeln!(f, "input {}", join(self.input.iter()))?;
+        writeln!(f, "output {}", join(self.output.iter()))
+    }
+}
+
+/// Parses a comma-separated list, where each item is parsed by `parse_item`.
+fn parse_list<T>(raw: &str, parse_item: impl Fn(&str) -> Result<T>) -> Result<Vec<T>> {
+    if raw.is_empty() {
+        return Ok(vec![]);
+    }
+    raw.split(',').map(|item| parse_item(item.trim())).collect()
+}
+
+fn parse_scalar<T: FromStr>(raw: &str) -> Result<T> {
+    raw.parse()
+        .map_err(|_| anyhow!("cannot parse snapshot value: {}", raw))
+}
+
+impl FromStr for Snapshot {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut lines = value.lines().filter(|line| !line.trim().is_empty());
+        let mut field = |key: &str| -> Result<&str> {
+            let line = lines
+                .next()
+                .ok_or_else(|| anyhow!("snapshot: missing {} line", key))?;
+            let (found, rest) = line.split_once(' ').unwrap_or((line, ""));
+            if found != key {
+                return Err(anyhow!("snapshot: expected {}, found: {}", key, line));
+            }
+            Ok(rest.trim())
+        };
+
+        let version: u32 = parse_scalar(field(SNAPSHOT_MAGIC)?)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(anyhow!("snapshot: unsupported version {}", version));
+        }
+        let program_counter = parse_scalar(field("program-counter")?)?;
+        let relative_base = parse_scalar(field("relative-base")?)?;
+        let cycles = parse_scalar(field("cycles")?)?;
+        let tape = parse_list(field("tape")?, parse_scalar)?;
+        let extended = parse_list(field("extended")?, |item| {
+            let (address, value) = item
+                .split_once('=')
+                .ok_or_else(|| anyhow!("snapshot: invalid extended word: {}", item))?;
+            Ok((parse_scalar(address)?, parse_scalar(value)?))
+        })?;
+        let input = parse_list(field("input")?, parse_scalar)?;
+        let output = parse_list(field("output")?, parse_scalar)?;
+
+        Ok(Self {
+            program_counter,
+            relative_base,
+            cycles,
+            tape,
+            extended: extended.into_iter().collect(),
+            input,
+            output,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::Snapshot;
+    use crate::intcode::{QueueDevice, RunStatus, VirtualMachine};
+
+    #[test]
+    fn test_snapshot_format() {
+        let snapshot = Snapshot {
+            program_counter: 2,
+            relative_base: -3,
+            cycles: 7,
+            tape: vec![1, -2, 99],
+            extended: [(1000, 5), (1003, -7)].into_iter().collect(),
+            input: vec![],
+            output: vec![4],
+        };
+        let text = snapshot.to_string();
+        assert_eq!(
+            text,
+            concat!(
+                "intcode-snapshot 1\n",
+                "program-counter 2\n",
+                "relative-base -3\n",
+                "cycles 7\n",
+                "tape 1,-2,99\n",
+                "extended 1000=5,1003=-7\n",
+                "input \n",
+                "output 4\n",
+            )
+        );
+        assert_eq!(text.parse::<Snapshot>().unwrap(), snapshot);
+        assert_eq!(snapshot.memory().get_scalar_at(1003), -7);
+
+        assert!("intcode-snapshot 2\n".parse::<Snapshot>().is_err());
+        assert!(text.replace("cycles", "steps").parse::<Snapshot>().is_err());
+        assert!(text.replace("1000=5", "1000").parse::<Snapshot>().is_err());
+        assert!(text.replace("output 4\n", "").parse::<Snapshot>().is_err());
+    }
+
+    #[test]
+    fn test_snapshot_resume() {
+        // reads two values and outputs their sum, twice
+        let tape = [3, 20, 3, 21, 1, 20, 21, 22, 4, 22, 1105, 1, 0];
+        let input = QueueDevice::from(vec![1, 2, 3]);
+        let mut vm = VirtualMachine::from_tape(&tape).with_input(input);
+        assert_eq!(vm.run().unwrap(), RunStatus::ProducedOutput(3));
+
+        let path = env::temp_dir().join("advent-2019-test-snapshot-resume.txt");
+        vm.snapshot().save(&path).unwrap();
+        let snapshot = Snapshot::load(&path).unwrap();
+        assert_eq!(snapshot.input, vec![3]);
+        assert_eq!(snapshot.output, vec![3]);
+        assert_eq!(snapshot.extended.len(), 3);
+
+        let mut resumed = VirtualMachine::from_snapshot(&snapshot);
+        assert_eq!(resumed.run().unwrap(), RunStatus::NeedsInput);
+        assert_eq!(resumed.cycles(), vm.cycles() + 2);
+
+        // resetting to the snapshot keeps the current devices
+        let input = QueueDevice::from(vec![10, 20]);
+        let mut vm = VirtualMachine::from_tape(&[99]).with_input(input);
+        vm.reset(&snapshot);
+        assert_eq!(vm.program_counter_snapshot(), snapshot.program_counter);
+        assert_eq!(vm.run().unwrap(), RunStatus::ProducedOutput(30));
+    }
+}
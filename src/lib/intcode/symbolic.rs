@@ -0,0 +1,400 @@
+//! Symbolic execution of straight-line `ADD`/`MUL` programs, such as the
+//! day 2 gravity assist program, with the noun and the verb left unknown.
+
+use std::{cmp::Reverse, collections::BTreeMap, fmt, ops::Range};
+
+use anyhow::{anyhow, Result};
+
+use super::{
+    instruction::{Instruction, Parameter},
+    memory::{MemoryBank, Scalar},
+};
+
+/// Polynomial in the noun and the verb.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Polynomial {
+    /// Structure: (noun exponent, verb exponent) -> non-zero coefficient
+    terms: BTreeMap<(u32, u32), Scalar>,
+}
+
+impl Polynomial {
+    pub fn constant(value: Scalar) -> Self {
+        Self::monomial((0, 0), value)
+    }
+
+    pub fn noun() -> Self {
+        Self::monomial((1, 0), 1)
+    }
+
+    pub fn verb() -> Self {
+        Self::monomial((0, 1), 1)
+    }
+
+    fn monomial(exponents: (u32, u32), coefficient: Scalar) -> Self {
+        let mut polynomial = Self::default();
+        if coefficient != 0 {
+            polynomial.terms.insert(exponents, coefficient);
+        }
+        polynomial
+    }
+
+    /// Returns `None` if the coefficient overflows.
+    fn add_term(&mut self, exponents: (u32, u32), coefficient: Scalar) -> Option<()> {
+        let sum = self
+            .terms
+            .get(&exponents)
+            .copied()
+            .unwrap_or(0)
+            .checked_add(coefficient)?;
+        if sum == 0 {
+            self.terms.remove(&exponents);
+        } else {
+            self.terms.insert(exponents, sum);
+        }
+        Some(())
+    }
+
+    /// Returns `None` if the polynomial depends on the noun or the verb.
+    pub fn as_constant(&self) -> Option<Scalar> {
+        match self.terms.iter().next() {
+            None => Some(0),
+            Some((&(0, 0), &value)) if self.terms.len() == 1 => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns `None` if a coefficient overflows.
+    pub fn add(&self, other: &Self) -> Option<Self> {
+        let mut sum = self.clone();
+        for (&exponents, &coefficient) in &other.terms {
+            sum.add_term(exponents, coefficient)?;
+        }
+        Some(sum)
+    }
+
+    /// Returns `None` if a coefficient or an exponent overflows.
+    pub fn multiply(&self, other: &Self) -> Option<Self> {
+        let mut product = Self::default();
+        for (&(lhs_noun, lhs_verb), &lhs) in &self.terms {
+            for (&(rhs_noun, rhs_verb), &rhs) in &other.terms {
+                let exponents = (
+                    lhs_noun.checked_add(rhs_noun)?,
+                    lhs_verb.checked_add(rhs_verb)?,
+                );
+                product.add_term(exponents, lhs.checked_mul(rhs)?)?;
+            }
+        }
+        Some(product)
+    }
+
+    /// Returns `None` if the value, or any intermediate result, overflows.
+    pub fn evaluate(&self, noun: Scalar, verb: Scalar) -> Option<Scalar> {
+        self.terms.iter().try_fold(
+            0,
+            |sum: Scalar, (&(noun_exponent, verb_exponent), &coefficient)| {
+                let term = coefficient
+                    .checked_mul(noun.checked_pow(noun_exponent)?)?
+                    .checked_mul(verb.checked_pow(verb_exponent)?)?;
+                sum.checked_add(term)
+            },
+        )
+    }
+
+    /// Non-zero coefficients of the polynomial in the verb once the noun is
+    /// fixed, indexed by the verb exponent.
+    ///
+    /// Returns `None` if a coefficient overflows.
+    fn in_verb(&self, noun: Scalar) -> Option<BTreeMap<u32, Scalar>> {
+        let mut coefficients = BTreeMap::new();
+        for (&(noun_exponent, verb_exponent), &coefficient) in &self.terms {
+            let term = coefficient.checked_mul(noun.checked_pow(noun_exponent)?)?;
+            let sum = coefficients.entry(verb_exponent).or_insert(0);
+            *sum = term.checked_add(*sum)?;
+        }
+        coefficients.retain(|_, coefficient| *coefficient != 0);
+        Some(coefficients)
+    }
+
+    /// Returns every (noun, verb) pair of the given ranges for which the
+    /// polynomial equals `target`, in increasing order.
+    ///
+    /// The verb is solved for directly whenever the polynomial is linear in
+    /// it, and scanned otherwise.
+    ///
+    /// Fails if evaluating the polynomial overflows for any pair.
+    pub fn solve(
+        &self,
+        target: Scalar,
+        nouns: Range<Scalar>,
+        verbs: Range<Scalar>,
+    ) -> Result<Vec<(Scalar, Scalar)>> {
+        let overflow =
+            |noun: Scalar| anyhow!("symbolic: overflow evaluating {} with noun {}", self, noun);
+        let mut solutions = vec![];
+        for noun in nouns {
+            let coefficients = self.in_verb(noun).ok_or_else(|| overflow(noun))?;
+            let constant = coefficients.get(&0).copied().unwrap_or(0);
+            match coefficients.keys().next_back() {
+                None | Some(0) if constant == target => {
+                    solutions.extend(verbs.clone().map(|verb| (noun, verb)))
+                }
+                None | Some(0) => {}
+                Some(1) => {
+                    let slope = coefficients[&1];
+                    let difference = target.checked_sub(constant).ok_or_else(|| overflow(noun))?;
+                    // `None` for `Scalar::MIN % -1`, whose quotient is no verb anyway
+                    if difference.checked_rem(slope) == Some(0)
+                        && verbs.contains(&(difference / slope))
+                    {
+                        solutions.push((noun, difference / slope));
+                    }
+                }
+                _ => {
+                    for verb in verbs.clone() {
+                        if self.evaluate(noun, verb).ok_or_else(|| overflow(noun))? == target {
+                            solutions.push((noun, verb));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(solutions)
+    }
+}
+
+/// Renders terms by decreasing degree, e.g. `230400*noun + verb + 797870`.
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        let mut terms: Vec<_> = self.terms.iter().collect();
+        terms.sort_by_key(|&(&(noun, verb), _)| Reverse((u64::from(noun) + u64::from(verb), noun)));
+        for (i, (&(noun_exponent, verb_exponent), &coefficient)) in terms.into_iter().enumerate() {
+            let mut factors = vec![];
+            if coefficient.unsigned_abs() != 1 || (noun_exponent, verb_exponent) == (0, 0) {
+                factors.push(coefficient.unsigned_abs().to_string());
+            }
+            for (name, exponent) in [("noun", noun_exponent), ("verb", verb_exponent)] {
+                match exponent {
+                    0 => {}
+                    1 => factors.push(name.to_string()),
+                    _ => factors.push(format!("{}^{}", name, exponent)),
+                }
+            }
+            let sign = match (i, coefficient < 0) {
+                (0, true) => "-",
+                (0, false) => "",
+                (_, true) => " - ",
+                (_, false) => " + ",
+            };
+            write!(f, "{}{}", sign, factors.join("*"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `memory` with the words at `noun_address` and `verb_address` left
+/// symbolic, and returns the polynomial left at `result_address` on halt.
+///
+/// Fails if the program executes anything but `ADD`, `MUL` and `HLT`, if an
+/// opcode or a write address depends on the noun or the verb, if the
+/// result depends on a word read at such an address, or if a coefficient
+/// overflows.
+pub fn symbolic_result(
+    memory: &MemoryBank,
+    noun_address: usize,
+    verb_address: usize,
+    result_address: usize,
+) -> Result<Polynomial> {
+    // `None` words were read at an address depending on the noun or verb
+    let mut words: BTreeMap<usize, Option<Polynomial>> = BTreeMap::new();
+    words.insert(noun_address, Some(Polynomial::noun()));
+    words.insert(verb_address, Some(Polynomial::verb()));
+    let read =
+        |words: &BTreeMap<usize, Option<Polynomial>>, address: usize| match words.get(&address) {
+            Some(word) => word.clone(),
+            None => Some(Polynomial::constant(memory.get_scalar_at(address))),
+        };
+    let concrete = |words: &BTreeMap<usize, Option<Polynomial>>, address: usize| {
+        read(words, address).and_then(|word| word.as_constant())
+    };
+
+    let mut pc = 0;
+    loop {
+        let code = concrete(&words, pc)
+            .ok_or_else(|| anyhow!("symbolic: pc {}: opcode depends on noun or verb", pc))?;
+        let instruction = Instruction::decode(pc, code, memory)?;
+        let (lhs, rhs) = match instruction {
+            Instruction::Add(lhs, rhs, _) | Instruction::Multiply(lhs, rhs, _) => (lhs, rhs),
+            Instruction::Halt => break,
+            _ => {
+                return Err(anyhow!(
+                    "symbolic: pc {}: unsupported instruction {}",
+                    pc,
+                    instruction.mnemonic()
+                ))
+            }
+        };
+
+        // `decode` read the parameters from the concrete memory: take them
+        // from the symbolic words instead
+        let operand = |index: usize, parameter: Parameter| -> Result<Option<Polynomial>> {
+            let word = read(&words, pc + index);
+            match parameter {
+                Parameter::Immediate(_) => Ok(word),
+                Parameter::Position(_) => Ok(word
+                    .and_then(|word| word.as_constant())
+                    .and_then(|address| usize::try_from(address).ok())
+                    .and_then(|address| read(&words, address))),
+                Parameter::Relative(_) => Err(anyhow!(
+                    "symbolic: pc {}: relative parameters are not supported",
+                    pc
+                )),
+            }
+        };
+        let (lhs, rhs) = (operand(1, lhs)?, operand(2, rhs)?);
+        let target = concrete(&words, pc + 3)
+            .and_then(|address| usize::try_from(address).ok())
+            .ok_or_else(|| anyhow!("symbolic: pc {}: write address depends on noun or verb", pc))?;
+
+        let value = match lhs.zip(rhs) {
+            Some((lhs, rhs)) => Some(
+                match instruction {
+                    Instruction::Add(_, _, _) => lhs.add(&rhs),
+                    _ => lhs.multiply(&rhs),
+                }
+                .ok_or_else(|| anyhow!("symbolic: pc {}: arithmetic overflow", pc))?,
+            ),
+            None => None,
+        };
+        words.insert(target, value);
+        pc += instruction.width();
+    }
+
+    read(&words, result_address).ok_or_else(|| {
+        anyhow!(
+            "symbolic: result at {} depends on memory addressed by noun or verb",
+            result_address
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{symbolic_result, Polynomial};
+    use crate::intcode::{assemble, MemoryBank, Scalar};
+
+    #[test]
+    fn test_polynomial_arithmetic() {
+        let noun = Polynomial::noun();
+        let verb = Polynomial::verb();
+        let polynomial = noun
+            .multiply(&Polynomial::constant(3))
+            .and_then(|product| product.add(&verb.multiply(&verb)?))
+            .and_then(|sum| sum.add(&Polynomial::constant(-4)))
+            .unwrap();
+        assert_eq!(polynomial.to_string(), "verb^2 + 3*noun - 4");
+        assert_eq!(polynomial.evaluate(2, 5), Some(27));
+        assert_eq!(polynomial.as_constant(), None);
+
+        let opposite = noun.multiply(&Polynomial::constant(-1)).unwrap();
+        let zero = noun.add(&opposite).unwrap();
+        assert_eq!(zero.as_constant(), Some(0));
+        assert_eq!(zero.to_string(), "0");
+        assert_eq!(Polynomial::constant(-7).to_string(), "-7");
+
+        let max = Polynomial::constant(Scalar::MAX);
+        assert_eq!(max.add(&Polynomial::constant(1)), None);
+        assert_eq!(max.multiply(&Polynomial::constant(2)), None);
+        assert_eq!(verb.multiply(&max).unwrap().evaluate(0, 2), None);
+        assert_eq!(
+            Polynomial::constant(Scalar::MIN).to_string(),
+            Scalar::MIN.to_string()
+        );
+    }
+
+    #[test]
+    fn test_polynomial_solve() {
+        let linear = Polynomial::noun()
+            .multiply(&Polynomial::constant(100))
+            .and_then(|product| product.add(&Polynomial::verb()))
+            .unwrap();
+        assert_eq!(linear.solve(1234, 0..100, 0..100).unwrap(), vec![(12, 34)]);
+        assert_eq!(linear.solve(12345, 0..100, 0..100).unwrap(), vec![]);
+
+        let quadratic = Polynomial::noun().multiply(&Polynomial::verb()).unwrap();
+        assert_eq!(
+            quadratic.solve(6, 0..10, 0..10).unwrap(),
+            vec![(1, 6), (2, 3), (3, 2), (6, 1)]
+        );
+        assert_eq!(
+            quadratic.solve(0, 0..2, 0..2).unwrap(),
+            vec![(0, 0), (0, 1), (1, 0)]
+        );
+
+        let opposite = Polynomial::verb()
+            .multiply(&Polynomial::constant(-1))
+            .unwrap();
+        assert_eq!(opposite.solve(Scalar::MIN, 0..1, 0..10).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_symbolic_result() {
+        let tape = assemble(
+            "
+                add 0, 0, 3         ; reads at the noun and verb addresses, then dead
+                add 1, 2, 3
+                mul 3, ten, 0
+                add 0, #5, 0
+                hlt
+            ten: data 10
+        ",
+        )
+        .unwrap();
+        let polynomial = symbolic_result(&MemoryBank::new(tape), 1, 2, 0).unwrap();
+        assert_eq!(polynomial.to_string(), "10*noun + 10*verb + 5");
+
+        for source in [
+            // the result is read at the noun address
+            "add 1, 2, 0\nhlt",
+            // the second write address becomes noun + verb
+            "add #0, #0, 7\nadd #0, #0, 0\nhlt",
+            // the second opcode becomes noun + verb
+            "add #0, #0, 4\nhlt",
+            "out 0\nhlt",
+        ] {
+            let tape = assemble(source).unwrap();
+            assert!(symbolic_result(&MemoryBank::new(tape), 1, 2, 0).is_err());
+        }
+    }
+
+    #[test]
+    fn test_symbolic_result_overflow() {
+        let tape = assemble(
+            "
+                add #0, #0, 100
+                mul big, big, big
+                mul big, big, big
+                mul big, big, big
+                add big, #0, 0
+                hlt
+            big: data 100000
+        ",
+        )
+        .unwrap();
+        assert!(symbolic_result(&MemoryBank::new(tape), 1, 2, 0).is_err());
+
+        // (noun * verb)^16 only overflows once evaluated
+        let source = format!("mul #0, #0, 0\n{}hlt", "mul 0, 0, 0\n".repeat(4));
+        let tape = assemble(&source).unwrap();
+        let polynomial = symbolic_result(&MemoryBank::new(tape), 1, 2, 0).unwrap();
+        assert_eq!(polynomial.to_string(), "noun^16*verb^16");
+        assert!(polynomial.solve(1, 0..100, 0..100).is_err());
+
+        // the exponents overflow
+        let source = format!("mul #0, #0, 0\n{}hlt", "mul 0, 0, 0\n".repeat(32));
+        let tape = assemble(&source).unwrap();
+        assert!(symbolic_result(&MemoryBank::new(tape), 1, 2, 0).is_err());
+    }
+}
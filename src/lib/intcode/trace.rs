@@ -0,0 +1,237 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use super::{instruction::Instruction, memory::Scalar};
+
+/// One executed instruction, as seen by a `Tracer`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceRecord {
+    /// Number of instructions executed before this one.
+    pub cycle: u64,
+    pub pc: usize,
+    /// Relative base at the time the instruction was executed.
+    pub relative_base: Scalar,
+    pub instruction: Instruction,
+    /// Values of the parameters read by the instruction, in tape order.
+    pub reads: Vec<Scalar>,
+    /// Structure: (address, value)
+    pub write: Option<(usize, Scalar)>,
+}
+
+impl TraceRecord {
+    /// Renders the record as a single JSON object.
+    ///
+    /// The rendered instruction only contains mnemonics, digits, `#`, `@`,
+    /// commas and spaces, so no string escaping is needed.
+    pub fn to_json(&self) -> String {
+        let reads: Vec<String> = self.reads.iter().map(|value| value.to_string()).collect();
+        let write = match self.write {
+            Some((address, value)) => format!("{{\"address\":{},\"value\":{}}}", address, value),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"cycle\":{},\"pc\":{},\"relative_base\":{},\"instruction\":\"{}\",\"reads\":[{}],\"write\":{}}}",
+            self.cycle,
+            self.pc,
+            self.relative_base,
+            self.instruction,
+            reads.join(","),
+            write
+        )
+    }
+}
+
+/// Text format, e.g. `3 4: MUL 3, 11, 0 | reads 70, 50 | [0] <- 3500`.
+impl fmt::Display for TraceRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.cycle, self.pc, self.instruction)?;
+        if !self.reads.is_empty() {
+            let reads: Vec<String> = self.reads.iter().map(|value| value.to_string()).collect();
+            write!(f, " | reads {}", reads.join(", "))?;
+        }
+        if let Some((address, value)) = self.write {
+            write!(f, " | [{}] <- {}", address, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One `TraceRecord` display per line.
+    Text,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+impl TraceFormat {
+    pub fn render(&self, record: &TraceRecord) -> String {
+        match self {
+            TraceFormat::Text => record.to_string(),
+            TraceFormat::JsonLines => record.to_json(),
+        }
+    }
+}
+
+/// Receives every instruction executed by a `VirtualMachine`.
+pub trait Tracer {
+    fn trace(&mut self, record: &TraceRecord);
+}
+
+/// In-memory trace.
+///
+/// Clones share the same records, so a handle can be kept to read the trace
+/// after giving the recorder to a machine.
+#[derive(Clone, Debug, Default)]
+pub struct TraceRecorder {
+    records: Rc<RefCell<Vec<TraceRecord>>>,
+}
+
+impl TraceRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn records(&self) -> Vec<TraceRecord> {
+        self.records.borrow().clone()
+    }
+
+    /// Renders the whole trace, one record per line.
+    pub fn render(&self, format: TraceFormat) -> String {
+        self.records
+            .borrow()
+            .iter()
+            .map(|record| format.render(record) + "\n")
+            .collect()
+    }
+}
+
+impl Tracer for TraceRecorder {
+    fn trace(&mut self, record: &TraceRecord) {
+        self.records.borrow_mut().push(record.clone());
+    }
+}
+
+/// Streams the trace to a writer, e.g. a log file.
+///
+/// Write errors are reported once on stderr, after which tracing stops.
+pub struct TraceWriter<W: Write> {
+    writer: W,
+    format: TraceFormat,
+    failed: bool,
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub fn new(writer: W, format: TraceFormat) -> Self {
+        Self {
+            writer,
+            format,
+            failed: false,
+        }
+    }
+}
+
+impl TraceWriter<io::Stderr> {
+    pub fn stderr(format: TraceFormat) -> Self {
+        Self::new(io::stderr(), format)
+    }
+}
+
+impl<W: Write> Tracer for TraceWriter<W> {
+    fn trace(&mut self, record: &TraceRecord) {
+        if self.failed {
+            return;
+        }
+        if let Err(error) = writeln!(self.writer, "{}", self.format.render(record)) {
+            eprintln!("cannot write Intcode trace: {}", error);
+            self.failed = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TraceFormat, TraceRecorder, TraceWriter, Tracer};
+    use crate::intcode::{QueueDevice, VirtualMachine};
+
+    #[test]
+    fn test_trace_day_2_example() {
+        let recorder = TraceRecorder::new();
+        let mut vm = VirtualMachine::from_tape(&[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50])
+            .with_tracer(recorder.clone());
+        vm.run_until_halt().unwrap();
+
+        assert_eq!(
+            recorder.render(TraceFormat::Text),
+            concat!(
+                "0 0: ADD 9, 10, 3 | reads 30, 40 | [3] <- 70\n",
+                "1 4: MUL 3, 11, 0 | reads 70, 50 | [0] <- 3500\n",
+                "2 8: HLT\n",
+            )
+        );
+        assert_eq!(
+            recorder.render(TraceFormat::JsonLines).lines().nth(1),
+            Some(concat!(
+                r#"{"cycle":1,"pc":4,"relative_base":0,"instruction":"MUL 3, 11, 0","#,
+                r#""reads":[70,50],"write":{"address":0,"value":3500}}"#
+            ))
+        );
+    }
+
+    #[test]
+    fn test_trace_reads_aliasing_the_target() {
+        let recorder = TraceRecorder::new();
+        let mut vm = VirtualMachine::from_tape(&[2, 3, 0, 3, 99]).with_tracer(recorder.clone());
+        vm.run_until_halt().unwrap();
+        let mut vm = VirtualMachine::from_tape(&[1, 5, 5, 5, 99, 7]).with_tracer(recorder.clone());
+        vm.run_until_halt().unwrap();
+
+        let records = recorder.records();
+        assert_eq!(
+            records[0].to_string(),
+            "0 0: MUL 3, 0, 3 | reads 3, 2 | [3] <- 6"
+        );
+        assert_eq!(
+            records[2].to_string(),
+            "0 0: ADD 5, 5, 5 | reads 7, 7 | [5] <- 14"
+        );
+    }
+
+    #[test]
+    fn test_trace_io_and_relative_mode() {
+        let recorder = TraceRecorder::new();
+        let mut vm = VirtualMachine::from_tape(&[109, 10, 203, 1, 204, 1, 99])
+            .with_input(QueueDevice::from(vec![42]))
+            .with_tracer(recorder.clone());
+        assert_eq!(vm.run_until_halt().unwrap(), vec![42]);
+
+        let records = recorder.records();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[1].relative_base, 10);
+        assert_eq!(records[1].reads, vec![]);
+        assert_eq!(records[1].write, Some((11, 42)));
+        assert_eq!(records[2].to_string(), "2 4: OUT @1 | reads 42");
+        assert!(records[2].to_json().ends_with("\"write\":null}"));
+    }
+
+    #[test]
+    fn test_trace_writer() {
+        let recorder = TraceRecorder::new();
+        let mut vm = VirtualMachine::from_tape(&[1101, 2, 3, 0, 99]).with_tracer(recorder.clone());
+        vm.run_until_halt().unwrap();
+
+        let mut log = vec![];
+        let mut writer = TraceWriter::new(&mut log, TraceFormat::Text);
+        for record in recorder.records() {
+            writer.trace(&record);
+        }
+        assert_eq!(
+            String::from_utf8(log).unwrap(),
+            "0 0: ADD #2, #3, 0 | reads 2, 3 | [0] <- 5\n1 4: HLT\n"
+        );
+    }
+}
@@ -0,0 +1,487 @@
+//! Ahead-of-time translation of Intcode tapes into Rust source.
+//!
+//! The instructions found by a static pass are decoded once and turned into
+//! the arms of a `match` on the program counter. Parameter words are still
+//! read from memory when the instruction runs, so a program rewriting them (as
+//! day 2's does) stays native: only instructions whose word was overwritten
+//! (as day 5 does with its first input) or that were not translated are
+//! handed over to the interpreter, one at a time.
+
+use std::collections::BTreeMap;
+
+use super::{
+    disassembler::{disassemble, Disassembled},
+    error::{IntcodeError, IntcodeResult},
+    flow_graph::BlockExit,
+    instruction::{Effect, Instruction, InstructionMode, Parameter},
+    io::{InputDevice, OutputDevice},
+    memory::{scalar_to_address, MemoryBank, Scalar},
+};
+
+/// Addresses listed per line of the generated `CODE` array.
+const CODE_ADDRESSES_PER_LINE: usize = 12;
+
+/// Decodes the instructions reachable from address 0 or from the start of
+/// a line of the disassembly, following immediate jump targets.
+///
+/// Anything else the program jumps to, or decodes differently once it
+/// rewrote it, is interpreted when the translated code runs.
+fn translated_instructions(memory: &MemoryBank) -> BTreeMap<usize, Instruction> {
+    let mut instructions = BTreeMap::new();
+    let mut pending: Vec<usize> = disassemble(memory)
+        .into_iter()
+        .filter(|line| matches!(line.content, Disassembled::Instruction(_)))
+        .map(|line| line.address)
+        .collect();
+    pending.push(0);
+    while let Some(pc) = pending.pop() {
+        if pc >= memory.tape_len() || instructions.contains_key(&pc) {
+            continue;
+        }
+        let instruction = match Instruction::decode(pc, memory.get_scalar_at(pc), memory) {
+            Ok(instruction) => instruction,
+            Err(_) => continue,
+        };
+        instructions.insert(pc, instruction);
+        match BlockExit::of(pc, instruction) {
+            Some(exit) => pending.extend(exit.successors()),
+            None => pending.push(pc + instruction.width()),
+        }
+    }
+    instructions
+}
+
+fn mode_name(parameter: Parameter) -> &'static str {
+    match parameter.mode() {
+        InstructionMode::Position => "Position",
+        InstructionMode::Immediate => "Immediate",
+        InstructionMode::Relative => "Relative",
+    }
+}
+
+/// Rust expression reading parameter `index` (0-based) of the instruction at `pc`.
+fn read(pc: usize, index: usize, parameter: Parameter) -> String {
+    format!("rt.read({}, {}, {})?", pc, index, mode_name(parameter))
+}
+
+/// Statements of a match arm writing `value` through parameter `index`,
+/// then moving on to `next`.
+fn write_then(pc: usize, index: usize, parameter: Parameter, value: &str, next: usize) -> String {
+    format!(
+        "                let value = {};\n                rt.write({}, {}, {}, value)?;\n                {}\n",
+        value,
+        pc,
+        index,
+        mode_name(parameter),
+        next
+    )
+}
+
+/// Match arm executing `instruction` at `pc`, evaluating to the next pc.
+fn translate_instruction(pc: usize, instruction: Instruction) -> String {
+    let next = pc + instruction.width();
+    let body = match instruction {
+        Instruction::Add(lhs, rhs, output_at) => write_then(
+            pc,
+            2,
+            output_at,
+            &format!(
+                "rt.add({}, {}, {})?",
+                pc,
+                read(pc, 0, lhs),
+                read(pc, 1, rhs)
+            ),
+            next,
+        ),
+        Instruction::Multiply(lhs, rhs, output_at) => write_then(
+            pc,
+            2,
+            output_at,
+            &format!(
+                "rt.multiply({}, {}, {})?",
+                pc,
+                read(pc, 0, lhs),
+                read(pc, 1, rhs)
+            ),
+            next,
+        ),
+        Instruction::LessThan(lhs, rhs, output_at) => write_then(
+            pc,
+            2,
+            output_at,
+            &format!("Scalar::from({} < {})", read(pc, 0, lhs), read(pc, 1, rhs)),
+            next,
+        ),
+        Instruction::Equals(lhs, rhs, output_at) => write_then(
+            pc,
+            2,
+            output_at,
+            &format!("Scalar::from({} == {})", read(pc, 0, lhs), read(pc, 1, rhs)),
+            next,
+        ),
+        Instruction::Input(output_at) => {
+            write_then(pc, 0, output_at, &format!("rt.input({})?", pc), next)
+        }
+        Instruction::Output(value) => format!(
+            "                rt.output({});\n                {}\n",
+            read(pc, 0, value),
+            next
+        ),
+        Instruction::JumpIfTrue(condition, target)
+        | Instruction::JumpIfFalse(condition, target) => {
+            let comparison = match instruction {
+                Instruction::JumpIfTrue(_, _) => "!=",
+                _ => "==",
+            };
+            format!(
+                "                if {} {} 0 {{\n                    rt.jump_target({}, {})?\n                }} else {{\n                    {}\n                }}\n",
+                read(pc, 0, condition),
+                comparison,
+                pc,
+                read(pc, 1, target),
+                next
+            )
+        }
+        Instruction::AdjustRelativeBase(offset) => format!(
+            "                rt.adjust_relative_base({}, {})?;\n                {}\n",
+            pc,
+            read(pc, 0, offset),
+            next
+        ),
+        Instruction::Halt => return format!("            // HLT\n            {} => break,\n", pc),
+    };
+    format!(
+        "            // {}\n            {} => {{\n{}            }}\n",
+        instruction, pc, body
+    )
+}
+
+/// Match arm handing the instruction at `pc` over to the interpreter.
+const INTERPRETED_ARM: &str = "rt.interpret(pc)? {
+                Some(next) => next,
+                None => break,
+            },
+";
+
+/// Translates the tape loaded in `memory` into a Rust module.
+///
+/// The module depends on this crate, and exposes
+/// `run(memory, input, output) -> IntcodeResult<MemoryBank>`, which behaves
+/// like `VirtualMachine::run_until_halt` on `memory` and returns the memory
+/// once the program halted. The memory may be patched beforehand, as long as
+/// its instruction words are left untouched.
+pub fn translate(memory: &MemoryBank) -> String {
+    let instructions = translated_instructions(memory);
+    let compares = instructions.values().any(|instruction| {
+        matches!(
+            instruction,
+            Instruction::LessThan(_, _, _) | Instruction::Equals(_, _, _)
+        )
+    });
+
+    let mut source = format!(
+        "//! Generated by `intcode-translator` from a {}-word tape: do not edit.\n\n",
+        memory.tape_len()
+    );
+    source += "use advent_2019_common::intcode::{\n";
+    source += "    InputDevice, InstructionMode::*, IntcodeResult, MemoryBank, OutputDevice,";
+    source += if compares { " Scalar,\n" } else { "\n" };
+    source += "    TranslatedRuntime,\n};\n\n";
+
+    source += "/// Addresses of the translated instruction words.\n";
+    source += &format!("const CODE: [usize; {}] = [\n", instructions.len());
+    let addresses: Vec<String> = instructions.keys().map(|pc| pc.to_string()).collect();
+    for line in addresses.chunks(CODE_ADDRESSES_PER_LINE) {
+        source += &format!("    {},\n", line.join(", "));
+    }
+    source += "];\n\n";
+
+    source += "pub fn run(\n";
+    source += "    memory: MemoryBank,\n";
+    source += "    input: impl InputDevice,\n";
+    source += "    output: impl OutputDevice,\n";
+    source += ") -> IntcodeResult<MemoryBank> {\n";
+    source += "    let mut rt = TranslatedRuntime::new(memory, &CODE, input, output);\n";
+    source += "    let mut pc = 0;\n";
+    source += "    loop {\n";
+    source += "        pc = match pc {\n";
+    source += "            _ if rt.is_patched(pc) => match ";
+    source += INTERPRETED_ARM;
+    for (&pc, &instruction) in &instructions {
+        source += &translate_instruction(pc, instruction);
+    }
+    source += "            _ => match ";
+    source += INTERPRETED_ARM;
+    source += "        };\n";
+    source += "    }\n";
+    source += "    Ok(rt.halt())\n";
+    source += "}\n";
+    source
+}
+
+/// Machine state of the code generated by `translate`.
+pub struct TranslatedRuntime<I, O> {
+    memory: MemoryBank,
+    relative_base: Scalar,
+    input: I,
+    output: O,
+    /// Whether a translated instruction starts at each address.
+    code: Vec<bool>,
+    /// Whether each translated instruction word was written since.
+    patched: Vec<bool>,
+}
+
+impl<I: InputDevice, O: OutputDevice> TranslatedRuntime<I, O> {
+    /// `code` holds the addresses of the translated instructions.
+    pub fn new(memory: MemoryBank, code: &[usize], input: I, output: O) -> Self {
+        let len = code.iter().max().map_or(0, |&address| address + 1);
+        let mut is_code = vec![false; len];
+        for &address in code {
+            is_code[address] = true;
+        }
+        Self {
+            memory,
+            relative_base: 0,
+            input,
+            output,
+            code: is_code,
+            patched: vec![false; len],
+        }
+    }
+
+    fn checked(&self, pc: usize, value: Option<Scalar>) -> IntcodeResult<Scalar> {
+        value.ok_or(IntcodeError::ArithmeticOverflow {
+            pc,
+            word: self.memory.get_scalar_at(pc),
+        })
+    }
+
+    fn to_address(&self, pc: usize, address: Scalar) -> IntcodeResult<usize> {
+        scalar_to_address(address).ok_or(IntcodeError::NegativeAddress {
+            pc,
+            word: self.memory.get_scalar_at(pc),
+            address,
+        })
+    }
+
+    /// Address designated by parameter `index` (0-based) of the instruction
+    /// at `pc`, or `None` in immediate mode.
+    fn parameter_address(
+        &self,
+        pc: usize,
+        index: usize,
+        mode: InstructionMode,
+    ) -> IntcodeResult<Option<usize>> {
+        let raw = self.memory.get_scalar_at(pc + 1 + index);
+        match mode {
+            InstructionMode::Position => self.to_address(pc, raw).map(Some),
+            InstructionMode::Immediate => Ok(None),
+            InstructionMode::Relative => {
+                let address = self.checked(pc, self.relative_base.checked_add(raw))?;
+                self.to_address(pc, address).map(Some)
+            }
+        }
+    }
+
+    /// Value of parameter `index` (0-based) of the instruction at `pc`.
+    pub fn read(&self, pc: usize, index: usize, mode: InstructionMode) -> IntcodeResult<Scalar> {
+        Ok(match self.parameter_address(pc, index, mode)? {
+            Some(address) => self.memory.get_scalar_at(address),
+            None => self.memory.get_scalar_at(pc + 1 + index),
+        })
+    }
+
+    /// Writes `value` through parameter `index` (0-based) of the instruction
+    /// at `pc`.
+    pub fn write(
+        &mut self,
+        pc: usize,
+        index: usize,
+        mode: InstructionMode,
+        value: Scalar,
+    ) -> IntcodeResult<()> {
+        let address =
+            self.parameter_address(pc, index, mode)?
+                .ok_or(IntcodeError::WriteInImmediateMode {
+                    pc,
+                    word: self.memory.get_scalar_at(pc),
+                    parameter: index + 1,
+                })?;
+        self.memory.set_scalar_at(address, value);
+        self.mark_patched(address);
+        Ok(())
+    }
+
+    fn mark_patched(&mut self, address: usize) {
+        if self.code.get(address).copied().unwrap_or(false) {
+            self.patched[address] = true;
+        }
+    }
+
+    /// Whether the translated instruction at `pc`, if any, was overwritten,
+    /// so that it must be interpreted instead.
+    pub fn is_patched(&self, pc: usize) -> bool {
+        self.patched.get(pc).copied().unwrap_or(false)
+    }
+
+    /// Reads the input device, failing like `VirtualMachine::run_until_halt`
+    /// when it is empty.
+    pub fn input(&mut self, pc: usize) -> IntcodeResult<Scalar> {
+        self.input.read().ok_or(IntcodeError::InputExhausted {
+            pc,
+            word: self.memory.get_scalar_at(pc),
+        })
+    }
+
+    pub fn output(&mut self, value: Scalar) {
+        self.output.write(value);
+    }
+
+    pub fn jump_target(&self, pc: usize, target: Scalar) -> IntcodeResult<usize> {
+        self.to_address(pc, target)
+    }
+
+    /// `lhs + rhs`, for the instruction at `pc`.
+    pub fn add(&self, pc: usize, lhs: Scalar, rhs: Scalar) -> IntcodeResult<Scalar> {
+        self.checked(pc, lhs.checked_add(rhs))
+    }
+
+    /// `lhs * rhs`, for the instruction at `pc`.
+    pub fn multiply(&self, pc: usize, lhs: Scalar, rhs: Scalar) -> IntcodeResult<Scalar> {
+        self.checked(pc, lhs.checked_mul(rhs))
+    }
+
+    pub fn adjust_relative_base(&mut self, pc: usize, offset: Scalar) -> IntcodeResult<()> {
+        self.relative_base = self.checked(pc, self.relative_base.checked_add(offset))?;
+        Ok(())
+    }
+
+    pub fn halt(self) -> MemoryBank {
+        self.memory
+    }
+
+    /// Decodes and executes the instruction at `pc` like the interpreter
+    /// does, returning the next program counter, or `None` on halt.
+    pub fn interpret(&mut self, pc: usize) -> IntcodeResult<Option<usize>> {
+        let word = self.memory.get_scalar_at(pc);
+        let instruction = Instruction::decode(pc, word, &self.memory)?;
+        let effect =
+            instruction.apply(pc, &mut self.memory, self.relative_base, &mut self.input)?;
+        if let Some(address) = instruction
+            .target()
+            .and_then(|target| target.address(self.relative_base))
+            .and_then(scalar_to_address)
+        {
+            self.mark_patched(address);
+        }
+        let next = pc + instruction.width();
+        Ok(match effect {
+            Effect::Continue => Some(next),
+            Effect::Jump(to) => Some(to),
+            Effect::AdjustRelativeBase(offset) => {
+                self.relative_base += offset;
+                Some(next)
+            }
+            Effect::Output(value) => {
+                self.output.write(value);
+                Some(next)
+            }
+            Effect::NeedsInput => return Err(IntcodeError::InputExhausted { pc, word }),
+            Effect::Halt => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{translate, translated_instructions, TranslatedRuntime};
+    use crate::intcode::{
+        assemble, InstructionMode::*, IntcodeError, MemoryBank, QueueDevice, Scalar,
+    };
+
+    #[test]
+    fn test_translated_instructions() {
+        let tape = assemble(
+            "
+                  in 100
+                  jf 100, #end
+                  jt 100, 100     ; dynamic target
+                  data 42
+                  out 100         ; only found by the disassembly
+            end:  hlt
+        ",
+        )
+        .unwrap();
+        let instructions = translated_instructions(&MemoryBank::new(tape));
+        assert_eq!(
+            instructions.keys().copied().collect::<Vec<_>>(),
+            vec![0, 2, 5, 9, 11]
+        );
+    }
+
+    #[test]
+    fn test_translate() {
+        let source = translate(&MemoryBank::new(vec![1002, 4, 3, 4, 4, 0, 99]));
+        assert!(source.starts_with("//! Generated by `intcode-translator` from a 7-word tape"));
+        assert!(source.contains("const CODE: [usize; 3] = [\n    0, 4, 6,\n];\n"));
+        assert!(source.contains(
+            "            // MUL 4, #3, 4\n            0 => {\n                let value = rt.multiply(0, rt.read(0, 0, Position)?, rt.read(0, 1, Immediate)?)?;\n                rt.write(0, 2, Position, value)?;\n                4\n            }\n"
+        ));
+        assert!(source.contains("            // HLT\n            6 => break,\n"));
+        assert!(!source.contains("Scalar"));
+    }
+
+    #[test]
+    fn test_translated_runtime() {
+        let output = QueueDevice::new();
+        // MUL 4, #3, 4 turns the word at 4 into HLT
+        let mut rt = TranslatedRuntime::new(
+            MemoryBank::new(vec![1002, 4, 3, 4, 33, -1]),
+            &[0, 4],
+            QueueDevice::from(vec![7]),
+            output.clone(),
+        );
+        let value = rt
+            .multiply(
+                0,
+                rt.read(0, 0, Position).unwrap(),
+                rt.read(0, 1, Immediate).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(value, 99);
+        assert!(!rt.is_patched(4));
+        rt.write(0, 2, Position, value).unwrap();
+        assert!(rt.is_patched(4));
+        assert!(!rt.is_patched(0));
+        assert_eq!(rt.interpret(4), Ok(None));
+
+        assert_eq!(rt.input(0), Ok(7));
+        assert_eq!(
+            rt.input(0),
+            Err(IntcodeError::InputExhausted { pc: 0, word: 1002 })
+        );
+        assert_eq!(
+            rt.jump_target(0, -1),
+            Err(IntcodeError::NegativeAddress {
+                pc: 0,
+                word: 1002,
+                address: -1
+            })
+        );
+        assert_eq!(
+            rt.add(0, Scalar::MAX, 1),
+            Err(IntcodeError::ArithmeticOverflow { pc: 0, word: 1002 })
+        );
+        rt.adjust_relative_base(0, 1).unwrap();
+        assert_eq!(
+            rt.adjust_relative_base(0, Scalar::MAX),
+            Err(IntcodeError::ArithmeticOverflow { pc: 0, word: 1002 })
+        );
+        assert_eq!(rt.read(0, 2, Relative).unwrap(), -1);
+        rt.output(5);
+        assert_eq!(output.drain(), vec![5]);
+
+        assert_eq!(rt.interpret(0), Ok(Some(4)));
+        assert_eq!(rt.halt().raw(), vec![1002, 4, 3, 4, 297, -1]);
+    }
+}
@@ -0,0 +1,1120 @@
+use std::collections::VecDeque;
+
+use super::{
+    decode_cache::DecodeCache,
+    error::{IntcodeError, IntcodeResult},
+    instruction::{Effect, Instruction, Parameter},
+    io::{InputDevice, OutputDevice, QueueDevice},
+    memory::{scalar_to_address, MemoryBank, Scalar},
+    profile::{global_profiling_enabled, merge_into_global_profile, Profile},
+    self_modification::SelfModification,
+    snapshot::Snapshot,
+    trace::{TraceRecord, Tracer},
+};
+
+/// Why `VirtualMachine::run` handed control back to its caller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunStatus {
+    /// The program executed a `HALT` instruction.
+    Halted,
+    /// The program is blocked on an `INPUT` instruction that the input device
+    /// cannot serve yet; running again retries it.
+    NeedsInput,
+    /// The program output a value, which was also forwarded to the output device.
+    ProducedOutput(Scalar),
+}
+
+/// State needed to undo one executed instruction.
+#[derive(Clone, Debug)]
+struct JournalEntry {
+    program_counter: usize,
+    relative_base: Scalar,
+    /// Structure: (address, old value), as returned by `MemoryBank::take_journal`.
+    writes: Vec<(usize, Scalar)>,
+}
+
+/// Full machine state, as compared by the loop detection.
+///
+/// Structure: (program counter, relative base, memory)
+type MachineState = (usize, Scalar, MemoryBank);
+
+/// Brent's cycle detection over full machine states: the state is saved
+/// every power of two instructions and compared against each later state.
+#[derive(Clone, Debug)]
+struct LoopDetector {
+    /// State the detection started from, replayed to find where a loop
+    /// starts once its period is known.
+    start: MachineState,
+    /// Structure: (program counter, relative base, memory, cycle)
+    saved: Option<(usize, Scalar, MemoryBank, u64)>,
+    power: u64,
+}
+
+impl LoopDetector {
+    /// Starts the detection from the state `(pc, relative_base, memory)`.
+    fn new(pc: usize, relative_base: Scalar, memory: &MemoryBank) -> Self {
+        Self {
+            start: (pc, relative_base, memory.clone()),
+            saved: None,
+            power: 1,
+        }
+    }
+
+    /// Returns the loop period if the state was already seen.
+    fn check(
+        &mut self,
+        pc: usize,
+        relative_base: Scalar,
+        memory: &MemoryBank,
+        cycle: u64,
+    ) -> Option<u64> {
+        if let Some((saved_pc, saved_relative_base, saved_memory, saved_cycle)) = &self.saved {
+            if *saved_pc == pc && *saved_relative_base == relative_base && saved_memory == memory {
+                return Some(cycle - saved_cycle);
+            }
+            if cycle - saved_cycle < self.power {
+                return None;
+            }
+            self.power *= 2;
+        } else {
+            self.power = 1;
+        }
+        // cheap, as tape pages are shared until written
+        self.saved = Some((pc, relative_base, memory.clone(), cycle));
+        None
+    }
+
+    /// Returns the program counter and instruction word of the first state
+    /// of the loop of `period` instructions the program is stuck in, by
+    /// replaying the execution from `start`.
+    fn loop_start(&self, period: u64) -> Option<(usize, Scalar)> {
+        let mut tortoise = self.start.clone();
+        let mut hare = self.start.clone();
+        for _ in 0..period {
+            hare = Self::next_state(hare)?;
+        }
+        while tortoise != hare {
+            tortoise = Self::next_state(tortoise)?;
+            hare = Self::next_state(hare)?;
+        }
+        let (pc, _, memory) = tortoise;
+        Some((pc, memory.get_scalar_at(pc)))
+    }
+
+    /// State following `state`, which cannot involve I/O nor halting since
+    /// the detection started from `start`.
+    fn next_state((pc, relative_base, mut memory): MachineState) -> Option<MachineState> {
+        let instruction = Instruction::decode(pc, memory.get_scalar_at(pc), &memory).ok()?;
+        let effect = instruction
+            .apply(pc, &mut memory, relative_base, &mut QueueDevice::new())
+            .ok()?;
+        memory.clear_journal();
+        let next = pc + instruction.width();
+        match effect {
+            Effect::Continue => Some((next, relative_base, memory)),
+            Effect::Jump(to) => Some((to, relative_base, memory)),
+            Effect::AdjustRelativeBase(offset) => Some((next, relative_base + offset, memory)),
+            Effect::Output(_) | Effect::NeedsInput | Effect::Halt => None,
+        }
+    }
+}
+
+pub struct VirtualMachine {
+    program_counter: usize,
+    relative_base: Scalar,
+    memory: MemoryBank,
+    input: Box<dyn InputDevice>,
+    output: Box<dyn OutputDevice>,
+    /// Number of instructions executed since the tape was loaded.
+    cycles: u64,
+    tracer: Option<Box<dyn Tracer>>,
+    /// One entry per executed instruction, when journaling is enabled.
+    journal: Option<VecDeque<JournalEntry>>,
+    /// Maximum number of entries in `journal`, if any.
+    journal_limit: Option<usize>,
+    /// Maximum value of `cycles`, if any.
+    step_limit: Option<u64>,
+    loop_detector: Option<LoopDetector>,
+    /// Whether memory was handed out through `memory_mut` since the last
+    /// step, making the loop detection restart at the next one.
+    patched: bool,
+    profile: Option<Profile>,
+    decode_cache: Option<DecodeCache>,
+    self_modification: Option<SelfModification>,
+}
+
+impl VirtualMachine {
+    /// Both I/O devices default to empty `QueueDevice`s.
+    pub fn from_tape(tape: &[Scalar]) -> Self {
+        Self::from_memory(MemoryBank::new(tape.to_vec()))
+    }
+
+    /// Runs on `memory` as loaded, e.g. a cheap clone of a shared bank.
+    pub fn from_memory(memory: MemoryBank) -> Self {
+        Self {
+            memory,
+            program_counter: 0,
+            relative_base: 0,
+            input: Box::new(QueueDevice::new()),
+            output: Box::new(QueueDevice::new()),
+            cycles: 0,
+            tracer: None,
+            journal: None,
+            journal_limit: None,
+            step_limit: None,
+            loop_detector: None,
+            patched: false,
+            profile: global_profiling_enabled().then(Profile::default),
+            decode_cache: None,
+            self_modification: None,
+        }
+    }
+
+    pub fn with_input(mut self, input: impl InputDevice + 'static) -> Self {
+        self.input = Box::new(input);
+        self
+    }
+
+    pub fn with_output(mut self, output: impl OutputDevice + 'static) -> Self {
+        self.output = Box::new(output);
+        self
+    }
+
+    /// Keeps an undo journal of every executed instruction, enabling
+    /// `step_back` and `rewind_to`.
+    pub fn with_journal(mut self) -> Self {
+        self.memory.enable_journal();
+        self.journal = Some(VecDeque::new());
+        self
+    }
+
+    /// Same as `with_journal`, forgetting the oldest instructions to keep
+    /// at most `limit` of them undoable.
+    pub fn with_journal_limit(mut self, limit: usize) -> Self {
+        self.journal_limit = Some(limit);
+        self.with_journal()
+    }
+
+    /// Fails with `IntcodeError::StepLimitExceeded` instead of executing more
+    /// than `limit` instructions since the tape was loaded.
+    pub fn with_step_limit(mut self, limit: u64) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    /// Fails with `IntcodeError::InfiniteLoop` once the whole machine state
+    /// (program counter, relative base and memory) repeats itself.
+    ///
+    /// Input and output instructions reset the detection, since a program
+    /// polling for input may legitimately go through the same state again,
+    /// and so do `step_back`, `rewind_to` and patches through `memory_mut`.
+    pub fn with_loop_detection(mut self) -> Self {
+        self.loop_detector = Some(LoopDetector::new(
+            self.program_counter,
+            self.relative_base,
+            &self.memory,
+        ));
+        self
+    }
+
+    /// Gathers execution statistics, available through `profile`.
+    pub fn with_profiling(mut self) -> Self {
+        self.profile.get_or_insert_with(Profile::default);
+        self
+    }
+
+    /// Decodes each instruction of the tape once, instead of at every step.
+    ///
+    /// Writes into decoded instructions, by the program or through
+    /// `memory_mut`, are tracked so that self-modifying programs stay correct.
+    /// Only patches through `memory_mut` need the memory journal, until the
+    /// next step.
+    pub fn with_decode_cache(self) -> Self {
+        let cache = DecodeCache::new(&self.memory);
+        self.with_shared_decode_cache(cache)
+    }
+
+    /// Same as `with_decode_cache`, with a cache built beforehand from the
+    /// same tape, e.g. once for every run of a search.
+    ///
+    /// Words written since the cache was built must have been invalidated
+    /// in it (see `DecodeCache::invalidate`).
+    pub fn with_shared_decode_cache(mut self, cache: DecodeCache) -> Self {
+        self.decode_cache = Some(cache);
+        self
+    }
+
+    /// Tracks the words executed as code and the instructions writing into
+    /// them, available through `self_modification`.
+    ///
+    /// Writes through `memory_mut` are not attributed to any instruction,
+    /// and `step_back` does not forget what was tracked.
+    pub fn with_self_modification_detection(mut self) -> Self {
+        self.memory.enable_journal();
+        self.self_modification = Some(SelfModification::default());
+        self
+    }
+
+    /// Reports every executed instruction to `tracer`.
+    pub fn with_tracer(mut self, tracer: impl Tracer + 'static) -> Self {
+        self.tracer = Some(Box::new(tracer));
+        self
+    }
+
+    /// Resumes a saved machine, with `QueueDevice`s holding its pending I/O.
+    pub fn from_snapshot(snapshot: &Snapshot) -> Self {
+        let mut vm = Self::from_tape(&[])
+            .with_input(QueueDevice::from(snapshot.input.clone()))
+            .with_output(QueueDevice::from(snapshot.output.clone()));
+        vm.reset(snapshot);
+        vm
+    }
+
+    /// Captures the machine state, including the pending I/O of devices able
+    /// to report it (see `InputDevice::pending`).
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            program_counter: self.program_counter,
+            relative_base: self.relative_base,
+            cycles: self.cycles,
+            tape: self.memory.raw(),
+            extended: self.memory.extended().clone(),
+            input: self.input.pending(),
+            output: self.output.pending(),
+        }
+    }
+
+    /// Restores the machine state saved in `snapshot`, e.g.
+    /// `Snapshot::from_tape` to start over.
+    ///
+    /// The I/O devices are kept as they are, so the snapshot's pending I/O is
+    /// ignored. The undo journal, if enabled, is cleared.
+    pub fn reset(&mut self, snapshot: &Snapshot) {
+        self.memory = snapshot.memory();
+        self.program_counter = snapshot.program_counter;
+        self.relative_base = snapshot.relative_base;
+        self.cycles = snapshot.cycles;
+        if self.journal.is_some() {
+            self.memory.enable_journal();
+            self.journal = Some(VecDeque::new());
+        }
+        if self.decode_cache.is_some() {
+            self.decode_cache = Some(DecodeCache::new(&self.memory));
+        }
+        if self.self_modification.is_some() {
+            self.memory.enable_journal();
+            self.self_modification = Some(SelfModification::default());
+        }
+        self.restart_loop_detection();
+    }
+
+    /// Restarts the loop detection, if enabled, from the current state.
+    fn restart_loop_detection(&mut self) {
+        if self.loop_detector.is_some() {
+            self.loop_detector = Some(LoopDetector::new(
+                self.program_counter,
+                self.relative_base,
+                &self.memory,
+            ));
+        }
+        self.patched = false;
+    }
+
+    /// Runs the program until it halts, blocks on input or outputs a value.
+    pub fn run(&mut self) -> IntcodeResult<RunStatus> {
+        loop {
+            if let Some(status) = self.step()? {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// Runs the program until it halts, returning every value it output.
+    ///
+    /// Fails if the program needs an input the input device cannot provide.
+    pub fn run_until_halt(&mut self) -> IntcodeResult<Vec<Scalar>> {
+        let mut outputs = vec![];
+        loop {
+            match self.run()? {
+                RunStatus::Halted => return Ok(outputs),
+                RunStatus::NeedsInput => {
+                    return Err(IntcodeError::InputExhausted {
+                        pc: self.program_counter,
+                        word: self.memory.get_scalar_at(self.program_counter),
+                    })
+                }
+                RunStatus::ProducedOutput(value) => outputs.push(value),
+            }
+        }
+    }
+
+    /// Decodes the instruction the program counter points to, without executing it.
+    pub fn current_instruction(&self) -> IntcodeResult<Instruction> {
+        let pc = self.program_counter;
+        if let Some(cache) = self.decode_cache.as_ref() {
+            // patches are only accounted for by the next `step`
+            let patched = self
+                .memory
+                .pending_journal()
+                .iter()
+                .any(|&(address, _)| cache.covers(pc, address));
+            if let Some(instruction) = cache.get(pc).filter(|_| !patched) {
+                return Ok(instruction);
+            }
+        }
+        let current_step = self.memory.get_scalar_at(self.program_counter);
+        Instruction::decode(self.program_counter, current_step, &self.memory)
+    }
+
+    /// Executes a single instruction.
+    ///
+    /// Returns `Some` status when the instruction requires handing control
+    /// back to the caller (see `run`).
+    pub fn step(&mut self) -> IntcodeResult<Option<RunStatus>> {
+        if let Some(limit) = self.step_limit.filter(|limit| self.cycles >= *limit) {
+            return Err(IntcodeError::StepLimitExceeded {
+                pc: self.program_counter,
+                word: self.memory.get_scalar_at(self.program_counter),
+                limit,
+            });
+        }
+        // writes made outside of `step` (e.g. patches) are not undoable
+        self.invalidate_decoded();
+        if self.patched {
+            self.restart_loop_detection();
+        }
+        self.memory.clear_journal();
+        if self.decode_cache.is_some() && self.journal.is_none() && self.self_modification.is_none()
+        {
+            // only enabled to track patches (see `memory_mut`)
+            self.memory.disable_journal();
+        }
+        let decoded_operation = self.current_instruction()?;
+        let reads = match self.tracer {
+            Some(_) => self.read_parameters(&decoded_operation),
+            None => vec![],
+        };
+        let effect = decoded_operation.apply(
+            self.program_counter,
+            &mut self.memory,
+            self.relative_base,
+            self.input.as_mut(),
+        )?;
+        if effect != Effect::NeedsInput {
+            // the only word an instruction may write is its target
+            let written = decoded_operation
+                .target()
+                .and_then(|target| target.address(self.relative_base))
+                .and_then(scalar_to_address);
+            if let (Some(cache), Some(address)) = (self.decode_cache.as_mut(), written) {
+                cache.invalidate(address);
+            }
+            if let Some(tracer) = self.tracer.as_mut() {
+                let write = written.map(|address| (address, self.memory.get_scalar_at(address)));
+                tracer.trace(&TraceRecord {
+                    cycle: self.cycles,
+                    pc: self.program_counter,
+                    relative_base: self.relative_base,
+                    instruction: decoded_operation,
+                    reads,
+                    write,
+                });
+            }
+            if let Some(detector) = self.self_modification.as_mut() {
+                detector.record_execution(self.program_counter, decoded_operation.width());
+                for &(address, _) in self.memory.pending_journal() {
+                    detector.record_write(self.program_counter, address);
+                }
+            }
+            if let Some(journal) = self.journal.as_mut() {
+                journal.push_back(JournalEntry {
+                    program_counter: self.program_counter,
+                    relative_base: self.relative_base,
+                    writes: self.memory.take_journal(),
+                });
+                if self
+                    .journal_limit
+                    .is_some_and(|limit| journal.len() > limit)
+                {
+                    journal.pop_front();
+                }
+            }
+            if let Some(profile) = self.profile.as_mut() {
+                profile.record(self.program_counter, decoded_operation);
+            }
+            self.cycles += 1;
+        }
+        self.memory.clear_journal();
+        let width = decoded_operation.width();
+        let status = match effect {
+            Effect::Continue => {
+                self.program_counter += width;
+                None
+            }
+            Effect::Jump(to) => {
+                self.program_counter = to;
+                None
+            }
+            Effect::AdjustRelativeBase(offset) => {
+                self.relative_base += offset;
+                self.program_counter += width;
+                None
+            }
+            Effect::Output(value) => {
+                self.output.write(value);
+                self.program_counter += width;
+                Some(RunStatus::ProducedOutput(value))
+            }
+            Effect::NeedsInput => Some(RunStatus::NeedsInput),
+            Effect::Halt => Some(RunStatus::Halted),
+        };
+
+        if let Some(detector) = self.loop_detector.as_mut() {
+            match decoded_operation {
+                // restart from the state right after the I/O
+                Instruction::Input(_) | Instruction::Output(_) => {
+                    *detector =
+                        LoopDetector::new(self.program_counter, self.relative_base, &self.memory);
+                }
+                Instruction::Halt => {}
+                _ => {
+                    let period = detector.check(
+                        self.program_counter,
+                        self.relative_base,
+                        &self.memory,
+                        self.cycles,
+                    );
+                    if let Some(period) = period {
+                        let (pc, word) = detector.loop_start(period).unwrap_or((
+                            self.program_counter,
+                            self.memory.get_scalar_at(self.program_counter),
+                        ));
+                        return Err(IntcodeError::InfiniteLoop { pc, word, period });
+                    }
+                }
+            }
+        }
+        Ok(status)
+    }
+
+    /// Makes the decode cache, if enabled, forget instructions overlapping
+    /// the patches pending in the memory journal.
+    fn invalidate_decoded(&mut self) {
+        if let Some(cache) = self.decode_cache.as_mut() {
+            for &(address, _) in self.memory.pending_journal() {
+                cache.invalidate(address);
+            }
+        }
+    }
+
+    /// Undoes the last executed instruction, restoring the memory it wrote,
+    /// the program counter and the relative base.
+    ///
+    /// Returns false if the journal is disabled or empty (see also
+    /// `with_journal_limit`). Values already
+    /// consumed from the input device or sent to the output device are not
+    /// given back.
+    pub fn step_back(&mut self) -> bool {
+        let undone = self.undo_step();
+        if undone {
+            self.restart_loop_detection();
+        }
+        undone
+    }
+
+    /// Same as `step_back`, leaving the loop detection as it is.
+    fn undo_step(&mut self) -> bool {
+        let entry = match self.journal.as_mut().and_then(VecDeque::pop_back) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        self.memory.undo(&entry.writes);
+        if let Some(cache) = self.decode_cache.as_mut() {
+            for &(address, _) in &entry.writes {
+                cache.invalidate(address);
+            }
+        }
+        self.program_counter = entry.program_counter;
+        self.relative_base = entry.relative_base;
+        self.cycles -= 1;
+        true
+    }
+
+    /// Steps back until `cycles()` equals `cycle`.
+    ///
+    /// Returns false, after rewinding as far as possible, if the journal does
+    /// not reach back that far or `cycle` is in the future.
+    pub fn rewind_to(&mut self, cycle: u64) -> bool {
+        while self.cycles > cycle && self.undo_step() {}
+        self.restart_loop_detection();
+        self.cycles == cycle
+    }
+
+    /// Values of the parameters `instruction` reads, skipping its write target.
+    fn read_parameters(&self, instruction: &Instruction) -> Vec<Scalar> {
+        let mut parameters = instruction.parameters();
+        // the write target, if any, is always the last parameter
+        if instruction.target().is_some() {
+            parameters.pop();
+        }
+        parameters
+            .into_iter()
+            .filter_map(|parameter| match parameter {
+                Parameter::Immediate(value) => Some(value),
+                _ => parameter
+                    .address(self.relative_base)
+                    .and_then(scalar_to_address)
+                    .map(|address| self.memory.get_scalar_at(address)),
+            })
+            .collect()
+    }
+
+    pub fn program_counter_snapshot(&self) -> usize {
+        self.program_counter
+    }
+
+    pub fn relative_base_snapshot(&self) -> Scalar {
+        self.relative_base
+    }
+
+    /// Number of instructions executed since the tape was loaded.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Statistics gathered since profiling was enabled, if it is.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Code executed and written into since detection was enabled, if it is.
+    pub fn self_modification(&self) -> Option<&SelfModification> {
+        self.self_modification.as_ref()
+    }
+
+    pub fn memory_snapshot(&self) -> &MemoryBank {
+        &self.memory
+    }
+
+    /// Gives write access to memory, e.g. to patch a running program.
+    pub fn memory_mut(&mut self) -> &mut MemoryBank {
+        self.patched = self.loop_detector.is_some();
+        if self.decode_cache.is_some() {
+            // the next step invalidates the patched instructions
+            self.memory.enable_journal();
+        }
+        &mut self.memory
+    }
+}
+
+/// Hands the statistics of the machine to the global profile, if enabled
+/// (see `enable_global_profiling`).
+impl Drop for VirtualMachine {
+    fn drop(&mut self) {
+        if let Some(profile) = self.profile.as_ref() {
+            if global_profiling_enabled() {
+                merge_into_global_profile(profile);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RunStatus, VirtualMachine};
+    use crate::intcode::{
+        CodeWrite, Instruction, IntcodeError, MemoryBank, Parameter::*, QueueDevice, Scalar,
+        Snapshot,
+    };
+
+    #[test]
+    fn test_virtual_machine_stepping() {
+        let tape_1 = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        let mut vm = VirtualMachine::from_tape(&tape_1);
+        assert_eq!(vm.memory_snapshot().raw(), &tape_1);
+
+        let tape_2 = [1, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50];
+        vm.step().unwrap();
+        assert_eq!(vm.memory_snapshot().raw(), &tape_2);
+
+        let tape_3 = [3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50];
+        vm.step().unwrap();
+        assert_eq!(vm.memory_snapshot().raw(), &tape_3);
+    }
+
+    #[test]
+    fn test_virtual_machine_running() {
+        let mut vm1 = VirtualMachine::from_tape(&[1, 0, 0, 0, 99]);
+        vm1.run_until_halt().unwrap();
+        assert_eq!(vm1.memory_snapshot().raw(), &[2, 0, 0, 0, 99]);
+
+        let mut vm2 = VirtualMachine::from_tape(&[2, 3, 0, 3, 99]);
+        vm2.run_until_halt().unwrap();
+        assert_eq!(vm2.memory_snapshot().raw(), &[2, 3, 0, 6, 99]);
+
+        let mut vm3 = VirtualMachine::from_tape(&[2, 4, 4, 5, 99, 0]);
+        vm3.run_until_halt().unwrap();
+        assert_eq!(vm3.memory_snapshot().raw(), &[2, 4, 4, 5, 99, 9801]);
+
+        let mut vm4 = VirtualMachine::from_tape(&[1, 1, 1, 4, 99, 5, 6, 0, 99]);
+        vm4.run_until_halt().unwrap();
+        assert_eq!(vm4.memory_snapshot().raw(), &[30, 1, 1, 4, 2, 5, 6, 0, 99]);
+    }
+
+    #[test]
+    fn test_virtual_machine_bug() {
+        let tape_1 = [1, 1, 1, 4, 99, 5, 6, 0, 99];
+        let mut vm = VirtualMachine::from_tape(&tape_1);
+        assert_eq!(vm.program_counter_snapshot(), 0);
+        assert_eq!(vm.memory_snapshot().raw(), &tape_1);
+
+        vm.step().unwrap();
+        assert_eq!(vm.program_counter_snapshot(), 4);
+        assert_eq!(vm.memory_snapshot().raw(), vec![1, 1, 1, 4, 2, 5, 6, 0, 99]);
+
+        vm.step().unwrap();
+        assert_eq!(vm.program_counter_snapshot(), 8);
+        assert_eq!(
+            vm.memory_snapshot().raw(),
+            vec![30, 1, 1, 4, 2, 5, 6, 0, 99]
+        );
+    }
+
+    #[test]
+    fn test_virtual_machine_input_output() {
+        let tape = [3, 0, 4, 0, 99];
+        let sink = QueueDevice::new();
+        let mut vm = VirtualMachine::from_tape(&tape)
+            .with_input(QueueDevice::from(vec![1]))
+            .with_output(sink.clone());
+        assert_eq!(vm.run_until_halt().unwrap(), vec![1]);
+        assert_eq!(sink.drain(), vec![1]);
+
+        let mut vm = VirtualMachine::from_tape(&tape);
+        assert_eq!(
+            vm.run_until_halt(),
+            Err(IntcodeError::InputExhausted { pc: 0, word: 3 })
+        );
+    }
+
+    #[test]
+    fn test_virtual_machine_variable_width_stepping() {
+        let mut vm =
+            VirtualMachine::from_tape(&[3, 0, 4, 0, 99]).with_input(QueueDevice::from(vec![7]));
+        assert_eq!(vm.step().unwrap(), None);
+        assert_eq!(vm.program_counter_snapshot(), 2);
+        assert_eq!(vm.memory_snapshot().raw(), &[7, 0, 4, 0, 99]);
+        assert_eq!(vm.step().unwrap(), Some(RunStatus::ProducedOutput(7)));
+        assert_eq!(vm.program_counter_snapshot(), 4);
+        assert_eq!(vm.step().unwrap(), Some(RunStatus::Halted));
+        assert_eq!(vm.program_counter_snapshot(), 4);
+
+        let mut vm = VirtualMachine::from_tape(&[1002, 4, 3, 4, 33]);
+        assert!(vm.run_until_halt().unwrap().is_empty());
+        assert_eq!(vm.memory_snapshot().raw(), &[1002, 4, 3, 4, 99]);
+        assert_eq!(vm.program_counter_snapshot(), 4);
+    }
+
+    #[test]
+    fn test_virtual_machine_signed_scalars() {
+        let memory = MemoryBank::try_from("1101,100,-1,4,0".to_string()).unwrap();
+        let mut vm = VirtualMachine::from_tape(&memory.raw());
+        vm.run_until_halt().unwrap();
+        assert_eq!(vm.memory_snapshot().raw(), &[1101, 100, -1, 4, 99]);
+
+        let mut vm = VirtualMachine::from_tape(&[1, -4, 0, 0, 99]);
+        assert_eq!(
+            vm.run_until_halt(),
+            Err(IntcodeError::NegativeAddress {
+                pc: 0,
+                word: 1,
+                address: -4
+            })
+        );
+
+        let mut vm = VirtualMachine::from_tape(&[1, 0, 0, 0, 42]);
+        assert_eq!(
+            vm.run_until_halt(),
+            Err(IntcodeError::UnknownOpcode { pc: 4, word: 42 })
+        );
+    }
+
+    #[test]
+    fn test_virtual_machine_arithmetic_overflow() {
+        let mut vm = VirtualMachine::from_tape(&[109, Scalar::MAX, 109, 1, 99]);
+        assert_eq!(
+            vm.run_until_halt(),
+            Err(IntcodeError::ArithmeticOverflow { pc: 2, word: 109 })
+        );
+        assert_eq!(vm.relative_base_snapshot(), Scalar::MAX);
+
+        let mut vm = VirtualMachine::from_tape(&[1101, Scalar::MAX, 1, 0, 99]);
+        assert_eq!(
+            vm.run_until_halt(),
+            Err(IntcodeError::ArithmeticOverflow { pc: 0, word: 1101 })
+        );
+        assert_eq!(vm.memory_snapshot().get_scalar_at(0), 1101);
+    }
+
+    #[test]
+    fn test_virtual_machine_comparisons() {
+        let programs: [(&[Scalar], [Scalar; 3]); 4] = [
+            // position mode, input equal to 8
+            (&[3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8], [0, 1, 0]),
+            // position mode, input less than 8
+            (&[3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8], [1, 0, 0]),
+            // immediate mode, input equal to 8
+            (&[3, 3, 1108, -1, 8, 3, 4, 3, 99], [0, 1, 0]),
+            // immediate mode, input less than 8
+            (&[3, 3, 1107, -1, 8, 3, 4, 3, 99], [1, 0, 0]),
+        ];
+        for (tape, expected) in programs {
+            for (input, expected_output) in [7, 8, 9].into_iter().zip(expected) {
+                let mut vm =
+                    VirtualMachine::from_tape(tape).with_input(QueueDevice::from(vec![input]));
+                assert_eq!(
+                    vm.run_until_halt().unwrap(),
+                    vec![expected_output],
+                    "tape: {:?}",
+                    tape
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_virtual_machine_jumps() {
+        let tapes: [&[Scalar]; 2] = [
+            &[3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9],
+            &[3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1],
+        ];
+        for tape in tapes {
+            for (input, expected_output) in [(0, 0), (5, 1), (-3, 1)] {
+                let mut vm =
+                    VirtualMachine::from_tape(tape).with_input(QueueDevice::from(vec![input]));
+                assert_eq!(
+                    vm.run_until_halt().unwrap(),
+                    vec![expected_output],
+                    "tape: {:?}",
+                    tape
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_virtual_machine_boost_examples() {
+        let quine = [
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let mut vm = VirtualMachine::from_tape(&quine);
+        assert_eq!(vm.run_until_halt().unwrap(), quine.to_vec());
+        assert_eq!(vm.relative_base_snapshot(), 16);
+
+        let mut vm = VirtualMachine::from_tape(&[1102, 34915192, 34915192, 7, 4, 7, 99, 0]);
+        assert_eq!(vm.run_until_halt().unwrap()[0].to_string().len(), 16);
+
+        let mut vm = VirtualMachine::from_tape(&[104, 1125899906842624, 99]);
+        assert_eq!(vm.run_until_halt().unwrap(), vec![1125899906842624]);
+    }
+
+    #[test]
+    fn test_virtual_machine_pausing() {
+        let input = QueueDevice::new();
+        let mut vm =
+            VirtualMachine::from_tape(&[3, 0, 4, 0, 3, 0, 4, 0, 99]).with_input(input.clone());
+        assert_eq!(vm.run().unwrap(), RunStatus::NeedsInput);
+        assert_eq!(vm.run().unwrap(), RunStatus::NeedsInput);
+        assert_eq!(vm.program_counter_snapshot(), 0);
+
+        input.push(5);
+        assert_eq!(vm.run().unwrap(), RunStatus::ProducedOutput(5));
+        assert_eq!(vm.run().unwrap(), RunStatus::NeedsInput);
+        input.push(6);
+        assert_eq!(vm.run().unwrap(), RunStatus::ProducedOutput(6));
+        assert_eq!(vm.run().unwrap(), RunStatus::Halted);
+        assert_eq!(vm.run().unwrap(), RunStatus::Halted);
+    }
+
+    #[test]
+    fn test_virtual_machine_cooperative_machines() {
+        // each machine adds its own input to the previous one's output
+        let tape = [3, 11, 3, 12, 1, 11, 12, 11, 4, 11, 99, 0, 0];
+        let (first_input, second_input) = (QueueDevice::from(vec![1]), QueueDevice::from(vec![2]));
+        let mut first = VirtualMachine::from_tape(&tape)
+            .with_input(first_input.clone())
+            .with_output(second_input.clone());
+        let mut second = VirtualMachine::from_tape(&tape).with_input(second_input);
+
+        assert_eq!(first.run().unwrap(), RunStatus::NeedsInput);
+        assert_eq!(second.run().unwrap(), RunStatus::NeedsInput);
+        first_input.push(10);
+        assert_eq!(first.run().unwrap(), RunStatus::ProducedOutput(11));
+        assert_eq!(second.run().unwrap(), RunStatus::ProducedOutput(13));
+        assert_eq!(first.run().unwrap(), RunStatus::Halted);
+        assert_eq!(second.run().unwrap(), RunStatus::Halted);
+    }
+
+    #[test]
+    fn test_virtual_machine_step_limit() {
+        let mut vm = VirtualMachine::from_tape(&[1105, 1, 0]).with_step_limit(10);
+        assert_eq!(
+            vm.run(),
+            Err(IntcodeError::StepLimitExceeded {
+                pc: 0,
+                word: 1105,
+                limit: 10
+            })
+        );
+        assert_eq!(vm.cycles(), 10);
+
+        let mut vm = VirtualMachine::from_tape(&[1101, 1, 1, 0, 99]).with_step_limit(2);
+        assert_eq!(vm.run(), Ok(RunStatus::Halted));
+    }
+
+    #[test]
+    fn test_virtual_machine_loop_detection() {
+        // counts to 5, then spins on `JT #1, #11`
+        let tape = [
+            1001, 14, 1, 14, 1008, 14, 5, 15, 1006, 15, 0, 1105, 1, 11, 0, 0,
+        ];
+        let mut vm = VirtualMachine::from_tape(&tape).with_loop_detection();
+        let error = vm.run().unwrap_err();
+        assert_eq!(
+            error,
+            IntcodeError::InfiniteLoop {
+                pc: 11,
+                word: 1105,
+                period: 1
+            }
+        );
+        assert_eq!(vm.memory_snapshot().get_scalar_at(14), 5);
+
+        // a prologue, then a loop over {4, 8} entered at 4
+        let tape = [1101, 0, 0, 20, 1001, 20, 0, 20, 1105, 1, 4];
+        let mut vm = VirtualMachine::from_tape(&tape).with_loop_detection();
+        assert_eq!(
+            vm.run(),
+            Err(IntcodeError::InfiniteLoop {
+                pc: 4,
+                word: 1001,
+                period: 2
+            })
+        );
+
+        // the loop starts right after an input
+        let tape = [3, 20, 1001, 20, 0, 20, 1105, 1, 2];
+        let mut vm = VirtualMachine::from_tape(&tape)
+            .with_input(QueueDevice::from(vec![1]))
+            .with_loop_detection();
+        assert_eq!(
+            vm.run(),
+            Err(IntcodeError::InfiniteLoop {
+                pc: 2,
+                word: 1001,
+                period: 2
+            })
+        );
+
+        // a loop toggling a word, so that its state repeats every other iteration
+        let tape = [1007, 8, 1, 8, 1105, 1, 0, 99, 0];
+        let mut vm = VirtualMachine::from_tape(&tape).with_loop_detection();
+        assert!(matches!(
+            vm.run(),
+            Err(IntcodeError::InfiniteLoop { period: 4, .. })
+        ));
+
+        // polling for input is not a loop
+        let input = QueueDevice::new();
+        let mut vm = VirtualMachine::from_tape(&[3, 5, 1105, 1, 0, 0])
+            .with_input(input.clone())
+            .with_loop_detection()
+            .with_step_limit(100);
+        for _ in 0..10 {
+            input.push(1);
+            assert_eq!(vm.run(), Ok(RunStatus::NeedsInput));
+        }
+
+        // counting loops never repeat a state
+        let mut vm = VirtualMachine::from_tape(&[1001, 5, 1, 5, 1105, 1, 0])
+            .with_loop_detection()
+            .with_step_limit(1000);
+        assert!(matches!(
+            vm.run(),
+            Err(IntcodeError::StepLimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_virtual_machine_loop_detection_restarts() {
+        // going back to an earlier state is not a loop
+        let tape = [1001, 5, 1, 5, 1105, 1, 0];
+        let mut vm = VirtualMachine::from_tape(&tape)
+            .with_journal()
+            .with_loop_detection();
+        for _ in 0..5 {
+            vm.step().unwrap();
+        }
+        assert!(vm.step_back());
+        assert!(vm.step_back());
+        assert_eq!(vm.step(), Ok(None));
+        assert!(vm.rewind_to(1));
+        assert_eq!(vm.step(), Ok(None));
+
+        // neither is patching memory back to it
+        let mut vm = VirtualMachine::from_tape(&tape).with_loop_detection();
+        for _ in 0..3 {
+            vm.step().unwrap();
+        }
+        vm.memory_mut().set_scalar_at(5, 2);
+        assert_eq!(vm.step(), Ok(None));
+    }
+
+    #[test]
+    fn test_virtual_machine_forked_memory() {
+        let base = MemoryBank::new(vec![1, 0, 0, 0, 99]);
+        for (noun, expected) in [(0, 2), (4, 100)] {
+            let mut memory = base.clone();
+            memory.set_scalar_at(1, noun);
+            let mut vm = VirtualMachine::from_memory(memory);
+            vm.run_until_halt().unwrap();
+            assert_eq!(vm.memory_snapshot().get_scalar_at(0), expected);
+        }
+        assert_eq!(base.raw(), vec![1, 0, 0, 0, 99]);
+    }
+
+    #[test]
+    fn test_virtual_machine_self_modification() {
+        // ADD 0, 0, 3 overwrites its own target, then ADD #98, #1, 8 writes
+        // the HLT executed next
+        let tape = [1, 0, 0, 3, 1101, 98, 1, 8, 0, 42];
+        let mut vm = VirtualMachine::from_tape(&tape).with_self_modification_detection();
+        vm.memory_mut().set_scalar_at(9, 43);
+        assert_eq!(vm.run(), Ok(RunStatus::Halted));
+
+        let detector = vm.self_modification().unwrap();
+        assert_eq!(
+            detector.writes().iter().copied().collect::<Vec<_>>(),
+            vec![
+                CodeWrite {
+                    writer: 0,
+                    target: 3,
+                    executed_after: false
+                },
+                CodeWrite {
+                    writer: 4,
+                    target: 8,
+                    executed_after: true
+                },
+            ]
+        );
+        assert_eq!(detector.code_ranges(), vec![0..9]);
+        assert!(!detector.is_code(9));
+
+        vm.reset(&Snapshot::from_tape(&tape));
+        assert!(vm.self_modification().unwrap().writes().is_empty());
+        assert!(VirtualMachine::from_tape(&tape)
+            .self_modification()
+            .is_none());
+    }
+
+    #[test]
+    fn test_virtual_machine_decode_cache() {
+        // ADD #98, #1, 4 turns the second instruction into HLT
+        let tape = [1101, 98, 1, 4, 1, 0, 0, 0];
+        let mut vm = VirtualMachine::from_tape(&tape).with_decode_cache();
+        assert_eq!(vm.run(), Ok(RunStatus::Halted));
+        assert_eq!(vm.cycles(), 2);
+
+        // patches are seen before the next step
+        let mut vm = VirtualMachine::from_tape(&[1, 0, 0, 0, 99]).with_decode_cache();
+        vm.memory_mut().set_scalar_at(0, 2);
+        assert_eq!(
+            vm.current_instruction(),
+            Ok(Instruction::Multiply(Position(0), Position(0), Position(0)))
+        );
+        vm.run().unwrap();
+        assert_eq!(vm.memory_snapshot().get_scalar_at(0), 4);
+
+        // undone writes, then a reset
+        let mut vm = VirtualMachine::from_tape(&tape)
+            .with_decode_cache()
+            .with_journal();
+        vm.run().unwrap();
+        assert!(vm.rewind_to(0));
+        assert_eq!(vm.memory_snapshot().raw(), &tape);
+        vm.step().unwrap();
+        assert_eq!(vm.current_instruction(), Ok(Instruction::Halt));
+        vm.reset(&Snapshot::from_tape(&tape));
+        assert_eq!(vm.run(), Ok(RunStatus::Halted));
+        assert_eq!(vm.cycles(), 2);
+    }
+
+    #[test]
+    fn test_virtual_machine_step_back() {
+        let tape = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        let mut vm = VirtualMachine::from_tape(&tape);
+        vm.step().unwrap();
+        assert!(!vm.step_back());
+
+        let mut vm = VirtualMachine::from_tape(&tape).with_journal();
+        assert!(!vm.step_back());
+        vm.run_until_halt().unwrap();
+        assert_eq!(vm.cycles(), 3);
+        assert_eq!(vm.memory_snapshot().get_scalar_at(0), 3500);
+
+        assert!(vm.step_back());
+        assert_eq!(vm.program_counter_snapshot(), 8);
+        assert!(vm.step_back());
+        assert_eq!(vm.program_counter_snapshot(), 4);
+        assert_eq!(
+            vm.memory_snapshot().raw(),
+            &[1, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]
+        );
+
+        // replaying from an earlier cycle gives the same result
+        vm.run_until_halt().unwrap();
+        assert_eq!(vm.memory_snapshot().get_scalar_at(0), 3500);
+        assert!(vm.rewind_to(0));
+        assert_eq!(vm.memory_snapshot().raw(), &tape);
+        assert_eq!(vm.cycles(), 0);
+    }
+
+    #[test]
+    fn test_virtual_machine_journal_limit() {
+        let tape = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        let mut vm = VirtualMachine::from_tape(&tape).with_journal_limit(2);
+        vm.run_until_halt().unwrap();
+        assert!(!vm.rewind_to(0));
+        assert_eq!(vm.cycles(), 1);
+        assert_eq!(vm.program_counter_snapshot(), 4);
+
+        let mut vm = VirtualMachine::from_tape(&tape).with_journal_limit(0);
+        vm.run_until_halt().unwrap();
+        assert!(!vm.step_back());
+    }
+
+    #[test]
+    fn test_virtual_machine_step_back_relative_mode() {
+        // ARB, then store the input beyond the tape
+        let mut vm = VirtualMachine::from_tape(&[109, 100, 203, 5, 99])
+            .with_input(QueueDevice::from(vec![42]))
+            .with_journal();
+        vm.run_until_halt().unwrap();
+        assert_eq!(vm.memory_snapshot().get_scalar_at(105), 42);
+
+        // patches are not journaled
+        vm.memory_mut().set_scalar_at(0, 1109);
+        assert!(vm.rewind_to(1));
+        assert_eq!(vm.relative_base_snapshot(), 100);
+        assert!(vm.memory_snapshot().extended().is_empty());
+        assert_eq!(vm.memory_snapshot().get_scalar_at(0), 1109);
+        assert!(!vm.rewind_to(5));
+        assert_eq!(vm.cycles(), 1);
+
+        vm.reset(&Snapshot::from_tape(&[99]));
+        assert!(!vm.step_back());
+        vm.run_until_halt().unwrap();
+        assert!(vm.step_back());
+    }
+}
@@ -1,14 +1,17 @@
 use std::fmt::Debug;
 use std::{
+    env,
     fs::File,
     io::{BufRead, BufReader},
     path::Path,
     time::Instant,
 };
 
-use anyhow::{Context, Error, Result};
+use anyhow::{anyhow, Context, Error, Result};
 use colored::*;
 
+pub mod intcode;
+
 fn parse_inputs_from_file<T, P>(path: P, separator: u8) -> Result<Vec<T>>
 where
     T: TryFrom<String, Error = Error>,
@@ -52,6 +55,27 @@ impl DayPuzzlePart {
     }
 }
 
+/// Path of the puzzle input of the given day, relative to the repository root.
+pub fn day_input_path(day_number: usize) -> String {
+    format!("./src/day-{}/input.txt", day_number)
+}
+
+/// Path of the tape designated by a command-line argument, either a day
+/// number or a tape file, failing with `usage` if the argument is missing.
+pub fn tape_path_from_arg(arg: Option<String>, usage: &str) -> Result<String> {
+    let arg = arg.ok_or_else(|| anyhow!("usage: {}", usage))?;
+    Ok(match arg.parse() {
+        Ok(day_number) => day_input_path(day_number),
+        Err(_) => arg,
+    })
+}
+
+/// Environment variable enabling the Intcode profiling report of
+/// `run_day_puzzle_solver`.
+pub const INTCODE_PROFILE_VARIABLE: &str = "INTCODE_PROFILE";
+
+/// Set `INTCODE_PROFILE` to print the hot spots of the Intcode machines run
+/// by `compute`.
 pub fn run_day_puzzle_solver<T, C, O>(
     day_number: usize,
     part: DayPuzzlePart,
@@ -70,20 +94,30 @@ where
 
     // Read input
     let input_start = Instant::now();
-    let parsed_input: Vec<T> =
-        parse_inputs_from_file(format!("./src/day-{}/input.txt", day_number), separator)?;
+    let parsed_input: Vec<T> = parse_inputs_from_file(day_input_path(day_number), separator)?;
     println!(
         "{}",
         format!("=> Input read in {:?}", input_start.elapsed()).cyan()
     );
 
     // Computing function
+    let profiling = env::var_os(INTCODE_PROFILE_VARIABLE).is_some();
+    if profiling {
+        intcode::enable_global_profiling();
+    }
     let compute_start = Instant::now();
-    let output = compute(parsed_input)?;
+    let output = compute(parsed_input);
+    // also stops profiling on failure, for the machines created afterwards
+    let profile = intcode::take_global_profile().filter(|_| profiling);
+    let output = output?;
     println!(
         "{}",
         format!("=> Computing done in {:?}", compute_start.elapsed()).cyan()
     );
+    if let Some(profile) = profile {
+        println!("{}", "=> Intcode profile:".cyan());
+        print!("{}", profile);
+    }
 
     // Output
     println!("{}\n", format!("=> Result = {:?}", output).green());